@@ -1,847 +1,273 @@
-#[macro_use]
-extern crate criterion;
-
-#[cfg(feature = "v8")]
-extern crate rust_wasm_c_api;
-#[cfg(feature = "v8")]
-use rust_wasm_c_api::*;
-
-extern crate wasmer_clif_backend;
-extern crate wasmer_singlepass_backend;
-extern crate wasmer_llvm_backend;
-extern crate wasmer_runtime_core;
-
-#[cfg(feature = "bench-wasmi")]
-extern crate wasmi;
-
-use std::str;
-
-use wasmer_runtime_core::{import::ImportObject, Func};
-
-static WASM: &'static [u8] = include_bytes!(
-    "../benchmarks/target/wasm32-unknown-unknown/release/wasm_bench_benchmarks.wasm"
-);
-
-static SMALL_WASM: &'static [u8] = include_bytes!("../benchmarks/src/printf.wasm");
-
-static LARGE_WASM: &'static [u8] = include_bytes!("../benchmarks/src/lua.wasm");
+use std::env;
+use std::path::Path;
+use std::process::{self, Command};
+use std::sync::Mutex;
 
 use criterion::*;
-use wasm_bench_benchmarks;
-use wasmer_clif_backend::CraneliftCompiler;
-use wasmer_singlepass_backend::SinglePassCompiler;
-use wasmer_llvm_backend::LLVMCompiler;
-
-#[cfg(feature = "bench-wasmi")]
-use wasmi::{ImportsBuilder, ModuleInstance, NopExternals, RuntimeValue};
-
-fn compile_benchmark(c: &mut Criterion) {
-    let mut small_benchmark = Benchmark::new("wasmer-clif", |b| {
-        let compiler = &CraneliftCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(SMALL_WASM, compiler).expect("should compile"),
-            )
-        })
-    })
-    .sample_size(10)
-    .throughput(Throughput::Bytes(SMALL_WASM.len() as u32))
-    .with_function("wasmer-llvm", |b| {
-        let compiler = &LLVMCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(SMALL_WASM, compiler).expect("should compile"),
-            )
-        })
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let compiler = &SinglePassCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(SMALL_WASM, compiler).expect("should compile"),
-            )
-        })
-    });
-
-    #[cfg(feature = "fast")]
-    {
-        small_benchmark = small_benchmark.sample_size(2);
+use wasm_bench::filter::Filter;
+use wasm_bench::perf;
+use wasm_bench::preflight;
+use wasm_bench::report::{self, CRITERION_DIR};
+use wasm_bench::results::{self, Results};
+use wasm_bench::runtime::{runtimes, try_runtimes};
+use wasm_bench::synthetic::{self, Parameter};
+use wasm_bench::workload::{check, workloads, Kind, Workload, NATIVE};
+
+/// Workload/runtime pairs benchmarked in this process, in order.
+static BENCHMARKED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Records that `workload` is benchmarked on `runtime`, so the results are
+/// built from this run only, not from everything in criterion's directory.
+/// Returns whether the pair is benchmarked for the first time.
+fn record(workload: &str, runtime: &str) -> bool {
+    let pair = (workload.to_string(), runtime.to_string());
+    let mut pairs = BENCHMARKED.lock().unwrap();
+    if pairs.contains(&pair) {
+        return false;
     }
-
-
-    c.bench("small_compile", small_benchmark);
-
-    let mut large_benchmark = Benchmark::new("wasmer-clif", |b| {
-        let compiler = &CraneliftCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(LARGE_WASM, compiler).expect("should compile"),
-            )
-        })
-    })
-    .sample_size(2)
-    .throughput(Throughput::Bytes(LARGE_WASM.len() as u32))
-    .with_function("wasmer-llvm", |b| {
-        let compiler = &LLVMCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(LARGE_WASM, compiler).expect("should compile"),
-            )
-        })
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let compiler = &SinglePassCompiler::new();
-        b.iter(|| {
-            black_box(
-                wasmer_runtime_core::compile_with(LARGE_WASM, compiler).expect("should compile"),
-            )
-        })
-    });
-
-
-    #[cfg(feature = "fast")]
-    {
-      large_benchmark = large_benchmark.sample_size(2);
-    }
-
-
-    c.bench("large_compile", large_benchmark);
+    pairs.push(pair);
+    true
 }
 
-#[cfg(feature = "v8")]
-mod wasm_c_api_support {
-    use rust_wasm_c_api::*;
-    pub struct WasmCApiEnv {
-        pub engine: *mut wasm_engine_t,
-        pub store: *mut wasm_store_t,
-        pub module: *mut wasm_module_t,
-        pub instance: *mut wasm_instance_t,
-        pub exports: *mut wasm_extern_vec_t,
-        pub export_types: *mut wasm_exporttype_vec_t,
+/// Sample sizes overriding criterion's default of 100.
+fn sample_size(workload: &str) -> Option<usize> {
+    if cfg!(feature = "fast") {
+        return Some(2);
     }
-    impl Drop for WasmCApiEnv {
-        fn drop(&mut self) {
-            unsafe {
-                wasm_extern_vec_delete(self.exports);
-                wasm_module_delete(self.module);
-                wasm_instance_delete(self.instance);
-                wasm_store_delete(self.store);
-                wasm_engine_delete(self.engine);
-                wasm_exporttype_vec_delete(self.export_types);
-            }
-        }
+    match workload {
+        "small_compile" => Some(10),
+        "large_compile" => Some(2),
+        _ if workload.starts_with("synthetic_") => Some(5),
+        // Every sample compiles `lua.wasm` again.
+        _ if workload.starts_with("lua_") => Some(5),
+        "fannkuch" | "fibonacci" | "nbody" if cfg!(feature = "bench-wasmi") => Some(25),
+        "sha1" if cfg!(feature = "bench-wasmi") => Some(20),
+        _ => None,
     }
+}
 
-    pub unsafe fn export_index_from_name(export_types: wasm_exporttype_vec_t, export_name: &str) -> usize {
-        use std::str;
-        use std::mem;
-        use std::slice;
-        let export_types_slice = slice::from_raw_parts(export_types.data, export_types.size);
-        let mut index: Option<usize> = None;
-        for (i, _item) in export_types_slice.iter().enumerate() {
-            let wasm_name = wasm_exporttype_name(export_types_slice[i]);
-            let name_bytes: &[u8] =
-                slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
-            let name = str::from_utf8_unchecked(name_bytes);
-            if name == export_name {
-                index = Some(i);
-                break;
-            }
-        }
-        let index = if let Some(idx) = index {
-            idx
-        } else {
-            panic!("export name {} not found: ", export_name);
-        };
-        index
+fn add_function<F>(benchmark: Option<Benchmark>, id: &str, f: F) -> Benchmark
+where
+    F: FnMut(&mut Bencher) + 'static,
+{
+    match benchmark {
+        Some(benchmark) => benchmark.with_function(id, f),
+        None => Benchmark::new(id, f),
     }
-
-
 }
 
-fn sum_benchmark(c: &mut Criterion) {
-    let mut benchmark = Benchmark::new("rust-native", |b| {
-        b.iter(|| black_box(wasm_bench_benchmarks::sum(1, 2)))
-    })
-    .with_function("wasmer-clif", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &CraneliftCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let sum: Func<(i32, i32), i32> = instance.func("sum").unwrap();
-        b.iter(|| black_box(sum.call(1, 2)))
-    })
-    .with_function("wasmer-llvm", |b| {
-        let module =
-            wasmer_runtime_core::compile_with(WASM, &LLVMCompiler::new()).expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let sum: Func<(i32, i32), i32> = instance.func("sum").unwrap();
-        b.iter(|| black_box(sum.call(1, 2)))
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &SinglePassCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let sum: Func<(i32, i32), i32> = instance.func("sum").unwrap();
-        b.iter(|| black_box(sum.call(1, 2)))
-    });
-
-    #[cfg(feature = "bench-wasmi")]
-    {
-        benchmark = benchmark.with_function("wasmi", |b| {
-            let module = wasmi::Module::from_buffer(WASM).expect("error loading wasm");
-            let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
-                .expect("error instantiating module")
-                .assert_no_start();
-            b.iter(|| {
-                black_box(instance.invoke_export(
-                    "sum",
-                    &[RuntimeValue::I32(1), RuntimeValue::I32(2)],
-                    &mut NopExternals,
-                ))
-            })
-        });
+fn bench_workload(c: &mut Criterion, workload: Workload, filter: &Filter) {
+    let name = workload.name;
+    let module = workload.module;
+    let expected = workload.expected();
+    let mut benchmark = None;
+
+    if let Some(native) = workload.native().filter(|_| filter.runtime(NATIVE)) {
+        benchmark = Some(add_function(benchmark, NATIVE, move |b| {
+            record(name, NATIVE);
+            perf::count(name, NATIVE, || black_box(native()));
+            b.iter(|| black_box(native()))
+        }));
     }
 
-    #[cfg(feature = "v8")]
-    {
-        use wasm_c_api_support::WasmCApiEnv;
-        unsafe {
-            use std::mem;
-            use std::slice;
-            // Instantiate wasm file
-            let engine = wasm_engine_new();
-            let store = wasm_store_new(engine);
-            let mut byte_vec = WASM.to_vec();
-            let bytes_slice: &mut [u8] = byte_vec.as_mut_slice();
-            let len = bytes_slice.len();
-            let bytes = wasm_byte_vec_t {
-                size: len,
-                data: bytes_slice.as_mut_ptr() as _,
-            };
-            let module = wasm_module_new(store, &bytes as *const wasm_byte_vec_t);
-            let imports = &[];
-            let instance = wasm_instance_new(store, module, imports.as_ptr());
-
-            // Get the export index for the name
-            let export_name = "sum";
-            let mut export_types: wasm_exporttype_vec_t = mem::uninitialized();
-            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
-            let export_types_slice = slice::from_raw_parts(export_types.data, export_types.size);
-            let mut index: Option<usize> = None;
-            for (i, _item) in export_types_slice.iter().enumerate() {
-                let wasm_name = wasm_exporttype_name(export_types_slice[i]);
-                let name_bytes: &[u8] =
-                    slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
-                let name = str::from_utf8_unchecked(name_bytes);
-                if name == export_name {
-                    index = Some(i);
-                    break;
-                }
+    for runtime in runtimes().into_iter().filter(|r| filter.runtime(r.name())) {
+        if !workload.runs_on(runtime.as_ref()) {
+            if workload.reports_unsupported() {
+                println!("{}/{}: unsupported", name, runtime.name());
             }
-            let index = if let Some(idx) = index {
-                idx
-            } else {
-                panic!("export name {} not found: ", export_name);
-            };
-
-            let mut exports: wasm_extern_vec_t = mem::uninitialized();
-            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
-            let data = exports.data;
-            let size = exports.size;
-            let exports_slice = slice::from_raw_parts(data, size);
-            let v8_func = wasm_extern_as_func(exports_slice[index]);
-
-            let env = WasmCApiEnv {
-                engine,
-                store,
-                module,
-                instance,
-                exports: &mut exports as *mut wasm_extern_vec_t,
-                export_types: &mut export_types as *mut wasm_exporttype_vec_t,
-            };
-
-            benchmark = benchmark.with_function("wasm-c-api-v8", move |b| {
-                let _env = &env;
-                let val1 = wasm_val_t__bindgen_ty_1 { i32: 3 };
-                let arg1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: val1,
-                };
-                let val2 = wasm_val_t__bindgen_ty_1 { i32: 4 };
-                let arg2 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: val2,
-                };
-                let args = [arg1, arg2];
-                let resval1 = wasm_val_t__bindgen_ty_1 { i32: 0 };
-                let res1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: resval1,
-                };
-                let results: &mut [wasm_val_t] = &mut [res1];
-                b.iter(|| black_box(wasm_func_call(v8_func, args.as_ptr(), results.as_mut_ptr())))
-            });
+            continue;
         }
+        let id = runtime.name().to_string();
+        let function = match workload.kind {
+            Kind::Compile => add_function(benchmark, &id, move |b| {
+                record(name, runtime.name());
+                let mut compile = || black_box(runtime.compile(module).expect("should compile"));
+                perf::count(name, runtime.name(), &mut compile);
+                b.iter(compile)
+            }),
+            Kind::Instantiate => add_function(benchmark, &id, move |b| {
+                let mut instantiate = runtime
+                    .prepare_instantiate(module)
+                    .expect("should compile");
+                if record(name, runtime.name()) {
+                    check(&mut instantiate, None).expect("should instantiate");
+                }
+                perf::count(name, runtime.name(), || black_box(instantiate()));
+                b.iter(|| black_box(instantiate()))
+            }),
+            Kind::Program { .. } => {
+                let workload = workload.clone();
+                add_function(benchmark, &id, move |b| {
+                    let mut setup = workload
+                        .prepare_program(runtime.as_ref())
+                        .expect("should compile");
+                    if record(name, runtime.name()) {
+                        check(&mut || setup()?(), None).expect("should run");
+                    }
+                    // The instances are dropped with the output, after the
+                    // measurement.
+                    perf::count_with_setup(
+                        name,
+                        runtime.name(),
+                        || setup().expect("should instantiate"),
+                        |mut run| (black_box(run()), run),
+                    );
+                    b.iter_batched(
+                        || setup().expect("should instantiate"),
+                        |mut run| (black_box(run()), run),
+                        BatchSize::PerIteration,
+                    )
+                })
+            }
+            Kind::Threads { .. } => {
+                let workload = workload.clone();
+                add_function(benchmark, &id, move |b| {
+                    let mut run = workload
+                        .prepare_threads(runtime.as_ref())
+                        .expect("should instantiate");
+                    if record(name, runtime.name()) {
+                        check(&mut run, expected).expect("should return the expected sum");
+                    }
+                    perf::count(name, runtime.name(), || black_box(run()));
+                    b.iter(|| black_box(run()))
+                })
+            }
+            Kind::Call { ref call, .. } => {
+                let call = call.clone();
+                let workload = workload.clone();
+                add_function(benchmark, &id, move |b| {
+                    let instance = workload
+                        .instantiate(runtime.as_ref())
+                        .expect("should instantiate and set up");
+                    let mut func = instance.prepare(&call).expect("export should resolve");
+                    if record(name, runtime.name()) {
+                        check(&mut func, expected).expect("should return the expected result");
+                    }
+                    perf::count(name, runtime.name(), || black_box(func()));
+                    b.iter(|| black_box(func()))
+                })
+            }
+        };
+        benchmark = Some(function);
     }
 
-    #[cfg(feature = "fast")]
-    {
-      benchmark = benchmark.sample_size(2);
+    let mut benchmark = match benchmark {
+        Some(benchmark) => benchmark,
+        None => return,
+    };
+    if workload.is_compile() {
+        benchmark = benchmark.throughput(Throughput::Bytes(module.len() as u32));
     }
-
-    c.bench("sum", benchmark);
+    if let Some(size) = sample_size(name) {
+        benchmark = benchmark.sample_size(size);
+    }
+    c.bench(name, benchmark);
 }
 
-fn fib_benchmark(c: &mut Criterion) {
-    let mut benchmark = Benchmark::new("rust-native", |b| {
-        b.iter(|| black_box(wasm_bench_benchmarks::fib(30)))
-    })
-    .with_function("wasmer-clif", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &CraneliftCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let fib: Func<(i64), i64> = instance.func("fib").unwrap();
-        b.iter(|| black_box(fib.call(30)))
-    })
-    .with_function("wasmer-llvm", |b| {
-        let module =
-            wasmer_runtime_core::compile_with(WASM, &LLVMCompiler::new()).expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let fib: Func<(i64), i64> = instance.func("fib").unwrap();
-        b.iter(|| black_box(fib.call(30)))
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &SinglePassCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let fib: Func<(i64), i64> = instance.func("fib").unwrap();
-        b.iter(|| black_box(fib.call(30)))
-    });
-
-    #[cfg(feature = "bench-wasmi")]
+/// Compiles the generated modules with `parameter` swept over its values on
+/// every runtime whose compilation is benchmarked.
+fn bench_synthetic(c: &mut Criterion, parameter: Parameter, filter: &Filter) {
+    let name = parameter.benchmark();
+    let mut benchmark: Option<ParameterizedBenchmark<u32>> = None;
+    for runtime in runtimes()
+        .into_iter()
+        .filter(|r| r.benches_compile() && filter.runtime(r.name()))
     {
-        benchmark = benchmark.sample_size(25).with_function("wasmi", |b| {
-            let module = wasmi::Module::from_buffer(WASM).expect("error loading wasm");
-            let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
-                .expect("error instantiating module")
-                .assert_no_start();
-            b.iter(|| {
-                black_box(instance.invoke_export(
-                    "fib",
-                    &[RuntimeValue::I64(30)],
-                    &mut NopExternals,
-                ))
-            })
+        let id = runtime.name().to_string();
+        let function = move |b: &mut Bencher, &value: &u32| {
+            let module = synthetic::generate(&parameter.shape(value));
+            b.iter(|| black_box(runtime.compile(&module).expect("should compile")))
+        };
+        benchmark = Some(match benchmark {
+            Some(benchmark) => benchmark.with_function(id, function),
+            None => ParameterizedBenchmark::new(id, function, parameter.values()),
         });
     }
 
-    #[cfg(feature = "v8")]
-    {
-        use wasm_c_api_support::WasmCApiEnv;
-        unsafe {
-            use std::mem;
-            use std::slice;
-            // Instantiate wasm file
-            let engine = wasm_engine_new();
-            let store = wasm_store_new(engine);
-            let mut byte_vec = WASM.to_vec();
-            let bytes_slice: &mut [u8] = byte_vec.as_mut_slice();
-            let len = bytes_slice.len();
-            let bytes = wasm_byte_vec_t {
-                size: len,
-                data: bytes_slice.as_mut_ptr() as _,
-            };
-            let module = wasm_module_new(store, &bytes as *const wasm_byte_vec_t);
-            let imports = &[];
-            let instance = wasm_instance_new(store, module, imports.as_ptr());
-
-            // Get the export index for the name
-            let export_name = "fib";
-            let mut export_types: wasm_exporttype_vec_t = mem::uninitialized();
-            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
-            let export_types_slice = slice::from_raw_parts(export_types.data, export_types.size);
-            let mut index: Option<usize> = None;
-            for (i, _item) in export_types_slice.iter().enumerate() {
-                let wasm_name = wasm_exporttype_name(export_types_slice[i]);
-                let name_bytes: &[u8] =
-                    slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
-                let name = str::from_utf8_unchecked(name_bytes);
-                if name == export_name {
-                    index = Some(i);
-                    break;
-                }
-            }
-            let index = if let Some(idx) = index {
-                idx
-            } else {
-                panic!("export name {} not found: ", export_name);
-            };
-
-            let mut exports: wasm_extern_vec_t = mem::uninitialized();
-            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
-            let data = exports.data;
-            let size = exports.size;
-            let exports_slice = slice::from_raw_parts(data, size);
-            let v8_func = wasm_extern_as_func(exports_slice[index]);
-
-            let env = WasmCApiEnv {
-                engine,
-                store,
-                module,
-                instance,
-                exports: &mut exports as *mut wasm_extern_vec_t,
-                export_types: &mut export_types as *mut wasm_exporttype_vec_t,
-            };
-
-            benchmark = benchmark.with_function("wasm-c-api-v8", move |b| {
-                let _env = &env;
-                let val1 = wasm_val_t__bindgen_ty_1 { i64: 30 };
-                let arg1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I64,
-                    of: val1,
-                };
-                let args = [arg1];
-                let resval1 = wasm_val_t__bindgen_ty_1 { i64: 0 };
-                let res1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I64,
-                    of: resval1,
-                };
-                let results: &mut [wasm_val_t] = &mut [res1];
-                b.iter(|| black_box(wasm_func_call(v8_func, args.as_ptr(), results.as_mut_ptr())))
-            });
-        }
-    }
-
-    #[cfg(feature = "fast")]
-    {
-      benchmark = benchmark.sample_size(2);
+    let mut benchmark = match benchmark {
+        Some(benchmark) => benchmark,
+        None => return,
+    };
+    benchmark = benchmark.throughput(move |&value| {
+        Throughput::Bytes(synthetic::generate(&parameter.shape(value)).len() as u32)
+    });
+    if let Some(size) = sample_size(name) {
+        benchmark = benchmark.sample_size(size);
     }
-
-    c.bench("fibonacci", benchmark);
+    c.bench(name, benchmark);
 }
 
-fn nbody_benchmark(c: &mut Criterion) {
-    let mut benchmark = Benchmark::new("rust-native", |b| {
-        b.iter(|| black_box(unsafe { wasm_bench_benchmarks::nbody::nbody_bench(5000) }))
-    })
-    .with_function("wasmer-clif", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &CraneliftCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        
-        let init_func: Func<()> = instance.func("init").unwrap();
-        init_func.call().unwrap();
-
-        let func: Func<(i32)> = instance.func("nbody_bench").unwrap();
-        b.iter(|| black_box(func.call(5000)))
-    })
-    .with_function("wasmer-llvm", |b| {
-        let module =
-            wasmer_runtime_core::compile_with(WASM, &LLVMCompiler::new()).expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-
-        let init_func: Func<()> = instance.func("init").unwrap();
-        init_func.call().unwrap();
-
-        let func: Func<(i32)> = instance.func("nbody_bench").unwrap();
-        b.iter(|| black_box(func.call(5000)))
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &SinglePassCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-
-        let init_func: Func<()> = instance.func("init").unwrap();
-        init_func.call().unwrap();
-
-        let func: Func<(i32)> = instance.func("nbody_bench").unwrap();
-        b.iter(|| black_box(func.call(5000)))
-    });
-
-    #[cfg(feature = "bench-wasmi")]
-    {
-        benchmark = benchmark.sample_size(25).with_function("wasmi", |b| {
-            let module = wasmi::Module::from_buffer(WASM).expect("error loading wasm");
-            let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
-                .expect("error instantiating module")
-                .assert_no_start();
-
-            instance.invoke_export(
-                    "init",
-                    &[],
-                    &mut NopExternals,
-                );
-
-            b.iter(|| {
-                black_box(instance.invoke_export(
-                    "nbody_bench",
-                    &[RuntimeValue::I32(5000)],
-                    &mut NopExternals,
-                ))
-            })
-        });
+fn benchmarks(c: &mut Criterion) {
+    let filter = Filter::from_env();
+    for workload in workloads().into_iter().filter(|w| filter.workload(w.name)) {
+        bench_workload(c, workload, &filter);
     }
-
-    #[cfg(feature = "v8")]
-    {
-        use wasm_c_api_support::*;
-        unsafe {
-            use std::mem;
-            use std::slice;
-            // Instantiate wasm file
-            let engine = wasm_engine_new();
-            let store = wasm_store_new(engine);
-            let mut byte_vec = WASM.to_vec();
-            let bytes_slice: &mut [u8] = byte_vec.as_mut_slice();
-            let len = bytes_slice.len();
-            let bytes = wasm_byte_vec_t {
-                size: len,
-                data: bytes_slice.as_mut_ptr() as _,
-            };
-            let module = wasm_module_new(store, &bytes as *const wasm_byte_vec_t);
-            let imports = &[];
-            let instance = wasm_instance_new(store, module, imports.as_ptr());
-
-            // Get the export index for the name
-            let mut export_types: wasm_exporttype_vec_t = mem::uninitialized();
-            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
-
-            let export_name = "nbody_bench";
-            let index = export_index_from_name(export_types, export_name);
-
-            let mut exports: wasm_extern_vec_t = mem::uninitialized();
-            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
-            let data = exports.data;
-            let size = exports.size;
-            let exports_slice = slice::from_raw_parts(data, size);
-            let v8_func = wasm_extern_as_func(exports_slice[index]);
-
-            let init_index = export_index_from_name(export_types, "init");
-            let init_args = [];
-            let init_results: &mut [wasm_val_t] = &mut [];
-            let init_func = wasm_extern_as_func(exports_slice[init_index]);
-            wasm_func_call(init_func, init_args.as_ptr(), init_results.as_mut_ptr());
-
-            let env = WasmCApiEnv {
-                engine,
-                store,
-                module,
-                instance,
-                exports: &mut exports as *mut wasm_extern_vec_t,
-                export_types: &mut export_types as *mut wasm_exporttype_vec_t,
-            };
-
-            benchmark = benchmark.with_function("wasm-c-api-v8", move |b| {
-                let _env = &env;
-                let val1 = wasm_val_t__bindgen_ty_1 { i32: 5000 };
-                let arg1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: val1,
-                };
-                let args = [arg1];
-                let results: &mut [wasm_val_t] = &mut [];
-                b.iter(|| black_box(wasm_func_call(v8_func, args.as_ptr(), results.as_mut_ptr())))
-            });
+    for &parameter in Parameter::ALL.iter() {
+        if filter.workload(parameter.benchmark()) {
+            bench_synthetic(c, parameter, &filter);
         }
     }
-
-    #[cfg(feature = "fast")]
-    {
-      benchmark = benchmark.sample_size(2);
-    }
-
-    c.bench("nbody", benchmark);
 }
 
-fn fannkuch_benchmark(c: &mut Criterion) {
-    let mut benchmark = Benchmark::new("rust-native", |b| {
-        b.iter(|| black_box(unsafe { wasm_bench_benchmarks::fannkuch_steps(5) }))
-    })
-    .with_function("wasmer-clif", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &CraneliftCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("fannkuch_steps").unwrap();
-        b.iter(|| black_box(func.call(5)))
-    })
-    .with_function("wasmer-llvm", |b| {
-        let module =
-            wasmer_runtime_core::compile_with(WASM, &LLVMCompiler::new()).expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("fannkuch_steps").unwrap();
-        b.iter(|| black_box(func.call(5)))
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &SinglePassCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("fannkuch_steps").unwrap();
-        b.iter(|| black_box(func.call(5)))
-    });
-
-    #[cfg(feature = "bench-wasmi")]
-    {
-        benchmark = benchmark.sample_size(25).with_function("wasmi", |b| {
-            let module = wasmi::Module::from_buffer(WASM).expect("error loading wasm");
-            let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
-                .expect("error instantiating module")
-                .assert_no_start();
-            b.iter(|| {
-                black_box(instance.invoke_export(
-                    "fannkuch_steps",
-                    &[RuntimeValue::I32(5)],
-                    &mut NopExternals,
-                ))
-            })
-        });
-    }
-
-    #[cfg(feature = "v8")]
-    {
-        use wasm_c_api_support::WasmCApiEnv;
-        unsafe {
-            use std::mem;
-            use std::slice;
-            // Instantiate wasm file
-            let engine = wasm_engine_new();
-            let store = wasm_store_new(engine);
-            let mut byte_vec = WASM.to_vec();
-            let bytes_slice: &mut [u8] = byte_vec.as_mut_slice();
-            let len = bytes_slice.len();
-            let bytes = wasm_byte_vec_t {
-                size: len,
-                data: bytes_slice.as_mut_ptr() as _,
-            };
-            let module = wasm_module_new(store, &bytes as *const wasm_byte_vec_t);
-            let imports = &[];
-            let instance = wasm_instance_new(store, module, imports.as_ptr());
-
-            // Get the export index for the name
-            let export_name = "fannkuch_steps";
-            let mut export_types: wasm_exporttype_vec_t = mem::uninitialized();
-            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
-            let export_types_slice = slice::from_raw_parts(export_types.data, export_types.size);
-            let mut index: Option<usize> = None;
-            for (i, _item) in export_types_slice.iter().enumerate() {
-                let wasm_name = wasm_exporttype_name(export_types_slice[i]);
-                let name_bytes: &[u8] =
-                    slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
-                let name = str::from_utf8_unchecked(name_bytes);
-                if name == export_name {
-                    index = Some(i);
-                    break;
-                }
-            }
-            let index = if let Some(idx) = index {
-                idx
-            } else {
-                panic!("export name {} not found: ", export_name);
-            };
+criterion_group!(benches, benchmarks);
 
-            let mut exports: wasm_extern_vec_t = mem::uninitialized();
-            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
-            let data = exports.data;
-            let size = exports.size;
-            let exports_slice = slice::from_raw_parts(data, size);
-            let v8_func = wasm_extern_as_func(exports_slice[index]);
-
-            let env = WasmCApiEnv {
-                engine,
-                store,
-                module,
-                instance,
-                exports: &mut exports as *mut wasm_extern_vec_t,
-                export_types: &mut export_types as *mut wasm_exporttype_vec_t,
-            };
-
-            benchmark = benchmark.with_function("wasm-c-api-v8", move |b| {
-                let _env = &env;
-                let val1 = wasm_val_t__bindgen_ty_1 { i32: 5 };
-                let arg1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: val1,
-                };
-                let args = [arg1];
-                let results: &mut [wasm_val_t] = &mut [];
-                b.iter(|| black_box(wasm_func_call(v8_func, args.as_ptr(), results.as_mut_ptr())))
+fn main() {
+    // Criterion parses the command line itself and rejects unknown flags, so
+    // `--runtime` and `--workload` are handed to a child through the
+    // environment instead.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (filter, rest) = Filter::from_args(&args).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+    if rest.len() != args.len() {
+        let status = env::current_exe()
+            .and_then(|exe| Command::new(exe).args(rest).envs(filter.to_env()).status())
+            .unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                process::exit(1);
             });
-        }
+        process::exit(status.code().unwrap_or(1));
     }
 
-    #[cfg(feature = "fast")]
-    {
-      benchmark = benchmark.sample_size(2);
+    if let Err(e) = try_runtimes() {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
-
-    c.bench("fannkuch", benchmark);
-}
-
-fn sha1_benchmark(c: &mut Criterion) {
-    let mut benchmark = Benchmark::new("rust-native", |b| {
-        b.iter(|| black_box(unsafe { wasm_bench_benchmarks::sha1(1000) }))
-    })
-    .with_function("wasmer-clif", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &CraneliftCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("sha1").unwrap();
-        b.iter(|| black_box(func.call(1000)))
-    })
-    .with_function("wasmer-llvm", |b| {
-        let module =
-            wasmer_runtime_core::compile_with(WASM, &LLVMCompiler::new()).expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("sha1").unwrap();
-        b.iter(|| black_box(func.call(1000)))
-    })
-    .with_function("wasmer-dynasm", |b| {
-        let module = wasmer_runtime_core::compile_with(WASM, &SinglePassCompiler::new())
-            .expect("should compile");
-        let instance = module
-            .instantiate(&ImportObject::new())
-            .expect("should instantiate");
-        let func: Func<(i32)> = instance.func("sha1").unwrap();
-        b.iter(|| black_box(func.call(1000)))
-    });
-
-    #[cfg(feature = "bench-wasmi")]
-    {
-        benchmark = benchmark.sample_size(20).with_function("wasmi", |b| {
-            let module = wasmi::Module::from_buffer(WASM).expect("error loading wasm");
-            let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
-                .expect("error instantiating module")
-                .assert_no_start();
-            b.iter(|| {
-                black_box(instance.invoke_export(
-                    "sha1",
-                    &[RuntimeValue::I32(1000)],
-                    &mut NopExternals,
-                ))
-            })
-        });
+    if let Err(e) = preflight::run_from_env() {
+        eprintln!("error: {}", e);
+        process::exit(1);
     }
-
-    #[cfg(feature = "v8")]
-    {
-        use wasm_c_api_support::WasmCApiEnv;
-        unsafe {
-            use std::mem;
-            use std::slice;
-            // Instantiate wasm file
-            let engine = wasm_engine_new();
-            let store = wasm_store_new(engine);
-            let mut byte_vec = WASM.to_vec();
-            let bytes_slice: &mut [u8] = byte_vec.as_mut_slice();
-            let len = bytes_slice.len();
-            let bytes = wasm_byte_vec_t {
-                size: len,
-                data: bytes_slice.as_mut_ptr() as _,
-            };
-            let module = wasm_module_new(store, &bytes as *const wasm_byte_vec_t);
-            let imports = &[];
-            let instance = wasm_instance_new(store, module, imports.as_ptr());
-
-            // Get the export index for the name
-            let export_name = "sha1";
-            let mut export_types: wasm_exporttype_vec_t = mem::uninitialized();
-            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
-            let export_types_slice = slice::from_raw_parts(export_types.data, export_types.size);
-            let mut index: Option<usize> = None;
-            for (i, _item) in export_types_slice.iter().enumerate() {
-                let wasm_name = wasm_exporttype_name(export_types_slice[i]);
-                let name_bytes: &[u8] =
-                    slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
-                let name = str::from_utf8_unchecked(name_bytes);
-                if name == export_name {
-                    index = Some(i);
-                    break;
-                }
+    benches();
+
+    let criterion = Criterion::default().configure_from_args();
+    let criterion_dir = Path::new(CRITERION_DIR);
+    let pairs = BENCHMARKED.lock().unwrap().clone();
+    match Results::from_criterion_pairs(criterion_dir, &pairs) {
+        Ok(mut results) => {
+            for entry in results.results.iter_mut() {
+                entry.counters = perf::per_iteration(&entry.workload, &entry.runtime);
+            }
+            if let Err(e) = results.write(&results::default_path()) {
+                eprintln!("failed to write results: {}", e);
+            }
+            if let Err(e) = report::print_table(&report::collect(&results, NATIVE), NATIVE)
+                .and_then(|()| report::print_metering_overhead(&results))
+            {
+                eprintln!("failed to print results: {}", e);
             }
-            let index = if let Some(idx) = index {
-                idx
-            } else {
-                panic!("export name {} not found: ", export_name);
-            };
-
-            let mut exports: wasm_extern_vec_t = mem::uninitialized();
-            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
-            let data = exports.data;
-            let size = exports.size;
-            let exports_slice = slice::from_raw_parts(data, size);
-            let v8_func = wasm_extern_as_func(exports_slice[index]);
-
-            let env = WasmCApiEnv {
-                engine,
-                store,
-                module,
-                instance,
-                exports: &mut exports as *mut wasm_extern_vec_t,
-                export_types: &mut export_types as *mut wasm_exporttype_vec_t,
-            };
-
-            benchmark = benchmark.with_function("wasm-c-api-v8", move |b| {
-                let _env = &env;
-                let val1 = wasm_val_t__bindgen_ty_1 { i32: 1000 };
-                let arg1 = wasm_val_t {
-                    kind: wasm_valkind_t_WASM_I32,
-                    of: val1,
-                };
-                let args = [arg1];
-                let results: &mut [wasm_val_t] = &mut [];
-                b.iter(|| black_box(wasm_func_call(v8_func, args.as_ptr(), results.as_mut_ptr())))
-            });
         }
+        Err(e) => eprintln!("failed to collect results: {}", e),
     }
-
-    #[cfg(feature = "fast")]
-    {
-      benchmark = benchmark.sample_size(2);
+    if let Err(e) = perf::report(criterion_dir) {
+        eprintln!("failed to report hardware counters: {}", e);
     }
-
-    c.bench("sha1", benchmark);
+    if let Err(e) = synthetic::report(criterion_dir, &filter) {
+        eprintln!("failed to report compile time scaling: {}", e);
+    }
+    criterion.final_summary();
 }
 
-// criterion_group!(benches, fannkuch_benchmark);
-
-criterion_group!(
-    benches,
-    fannkuch_benchmark,
-    fib_benchmark,
-    sha1_benchmark,
-    sum_benchmark,
-    nbody_benchmark,
-    compile_benchmark
-);
-criterion_main!(benches);
-
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,47 @@
+//! Deterministic instruction counts for every workload and runtime.
+//!
+//! `cargo +nightly bench --bench icount`
+
+use std::env;
+use std::process;
+
+use wasm_bench::filter::Filter;
+use wasm_bench::icount;
+use wasm_bench::runtime::try_runtimes;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if let Some(position) = args.iter().position(|arg| arg == icount::CHILD_FLAG) {
+        if let Err(e) = icount::run_child(&args[position + 1..]) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let filter = match Filter::from_args(&args[1..]) {
+        Ok((filter, _)) => filter,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = try_runtimes() {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+    match icount::run(&filter) {
+        Ok(0) => {}
+        Ok(changed) => {
+            eprintln!(
+                "error: {} instruction counts changed by more than the threshold",
+                changed
+            );
+            process::exit(icount::CHANGED_EXIT_CODE);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
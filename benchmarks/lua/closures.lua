@@ -0,0 +1,39 @@
+-- Closure-heavy: creating closures over upvalues, higher-order functions and
+-- coroutines.
+local function counter()
+  local n = 0
+  return function()
+    n = n + 1
+    return n
+  end
+end
+
+local function map(list, f)
+  local result = {}
+  for i = 1, #list do
+    result[i] = f(list[i])
+  end
+  return result
+end
+
+local list = {}
+for i = 1, 2000 do
+  list[i] = counter()
+end
+for _ = 1, 10 do
+  list = map(list, function(next)
+    next()
+    return next
+  end)
+end
+
+local generator = coroutine.wrap(function()
+  for i = 1, 20000 do
+    coroutine.yield(i)
+  end
+end)
+local sum = 0
+for _ = 1, 20000 do
+  sum = sum + generator()
+end
+assert(list[1]() == 11 and sum == 200010000)
@@ -0,0 +1,35 @@
+-- Numeric loops: integer and floating point arithmetic, as in a sieve and a
+-- Mandelbrot set.
+local limit = 100000
+local composite = {}
+local primes = 0
+for i = 2, limit do
+  if not composite[i] then
+    primes = primes + 1
+    for j = i * i, limit, i do
+      composite[j] = true
+    end
+  end
+end
+
+local size = 64
+local inside = 0
+for y = 0, size - 1 do
+  local ci = 2.0 * y / size - 1.0
+  for x = 0, size - 1 do
+    local cr = 2.0 * x / size - 1.5
+    local zr, zi = 0.0, 0.0
+    local escaped = false
+    for _ = 1, 50 do
+      zr, zi = zr * zr - zi * zi + cr, 2.0 * zr * zi + ci
+      if zr * zr + zi * zi > 4.0 then
+        escaped = true
+        break
+      end
+    end
+    if not escaped then
+      inside = inside + 1
+    end
+  end
+end
+assert(primes == 9592 and inside > 0)
@@ -0,0 +1,16 @@
+-- String-heavy: formatting, concatenation, pattern matching and substitution.
+local parts = {}
+for i = 1, 5000 do
+  parts[#parts + 1] = string.format("%05d:%x:%s", i, i * 31, ("ab"):rep(i % 5))
+end
+local text = table.concat(parts, ",")
+
+local count = 0
+for number, hex in text:gmatch("(%d+):(%x+)") do
+  if tonumber(hex, 16) % 2 == 0 then
+    count = count + 1
+  end
+end
+
+local upper = text:gsub("ab", "AB"):upper()
+assert(count > 0 and #upper == #text)
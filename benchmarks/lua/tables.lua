@@ -0,0 +1,23 @@
+-- Table-heavy: array and hash inserts, lookups, removal and sorting.
+local array = {}
+for i = 1, 20000 do
+  array[i] = (i * 7919) % 10007
+end
+table.sort(array)
+
+local hash = {}
+for i = 1, #array do
+  hash["k" .. array[i]] = i
+end
+
+local found = 0
+for i = 1, 20000 do
+  if hash["k" .. i] then
+    found = found + 1
+  end
+end
+
+while #array > 10000 do
+  table.remove(array)
+end
+assert(found > 0 and #array == 10000)
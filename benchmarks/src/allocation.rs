@@ -0,0 +1,50 @@
+//! Allocation-heavy workloads, to compare the cost of guest allocators.
+//!
+//! The wasm module is built once with the default allocator and, with the
+//! `wee_alloc` feature, once with `wee_alloc`.
+
+use std::collections::HashMap;
+
+use crate::binarytrees::{bottom_up_tree, item_check};
+
+/// Allocates and frees `iterations` binary trees of `depth` levels, one node
+/// per allocation.
+pub fn alloc_trees(depth: i32, iterations: i32) -> i32 {
+    (0..iterations)
+        .map(|_| item_check(&bottom_up_tree(depth)))
+        .sum()
+}
+
+/// Grows and shrinks vectors, strings and a hash map `n` times.
+pub fn alloc_churn(n: i32) -> i32 {
+    let mut checksum = 0;
+    for i in 0..n {
+        let mut map = HashMap::new();
+        let mut vectors = Vec::new();
+        for j in 0..100 {
+            let mut vector = Vec::new();
+            for k in 0..j {
+                vector.push(i ^ k);
+            }
+            map.insert(format!("key{}", j), vector.len());
+            vectors.push(vector);
+        }
+        for j in (0..100).step_by(2) {
+            map.remove(&format!("key{}", j));
+            vectors[j].clear();
+            vectors[j].shrink_to_fit();
+        }
+        checksum ^= map.len() as i32 + vectors.iter().map(Vec::len).sum::<usize>() as i32;
+    }
+    checksum
+}
+
+#[no_mangle]
+pub extern "C" fn alloc_trees_bench(depth: i32) -> i32 {
+    alloc_trees(depth, 10)
+}
+
+#[no_mangle]
+pub extern "C" fn alloc_churn_bench(n: i32) -> i32 {
+    alloc_churn(n)
+}
@@ -0,0 +1,81 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/binarytrees-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/rayon/typed_arena, allocate every node with Box,
+// write to a buffer, add fn binary_trees_bench
+
+use std::fmt::Write;
+
+use crate::checksum;
+
+pub(crate) struct Tree {
+    children: Option<(Box<Tree>, Box<Tree>)>,
+}
+
+pub(crate) fn item_check(tree: &Tree) -> i32 {
+    match tree.children {
+        Some((ref left, ref right)) => 1 + item_check(left) + item_check(right),
+        None => 1,
+    }
+}
+
+pub(crate) fn bottom_up_tree(depth: i32) -> Box<Tree> {
+    let children = if depth > 0 {
+        Some((bottom_up_tree(depth - 1), bottom_up_tree(depth - 1)))
+    } else {
+        None
+    };
+    Box::new(Tree { children })
+}
+
+/// Allocates and walks binary trees of up to `n` levels, returning the
+/// program's output.
+pub fn binary_trees(n: i32) -> String {
+    let min_depth = 4;
+    let max_depth = if min_depth + 2 > n { min_depth + 2 } else { n };
+    let mut out = String::new();
+
+    let stretch_depth = max_depth + 1;
+    let stretch = bottom_up_tree(stretch_depth);
+    writeln!(
+        out,
+        "stretch tree of depth {}\t check: {}",
+        stretch_depth,
+        item_check(&stretch)
+    )
+    .unwrap();
+    drop(stretch);
+
+    let long_lived = bottom_up_tree(max_depth);
+
+    for depth in (min_depth..max_depth + 1).step_by(2) {
+        let iterations = 1 << (max_depth - depth + min_depth);
+        let check: i32 = (0..iterations)
+            .map(|_| item_check(&bottom_up_tree(depth)))
+            .sum();
+        writeln!(
+            out,
+            "{}\t trees of depth {}\t check: {}",
+            iterations, depth, check
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "long lived tree of depth {}\t check: {}",
+        max_depth,
+        item_check(&long_lived)
+    )
+    .unwrap();
+    out
+}
+
+#[no_mangle]
+pub extern "C" fn binary_trees_bench(n: i32) -> i32 {
+    checksum(binary_trees(n).as_bytes())
+}
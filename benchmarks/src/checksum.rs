@@ -0,0 +1,7 @@
+/// 32-bit FNV-1a hash of `bytes`. The Benchmarks Game exports return it for
+/// their output, which a runtime cannot then skip producing.
+pub fn checksum(bytes: &[u8]) -> i32 {
+    bytes.iter().fold(0x811c_9dc5u32, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    }) as i32
+}
@@ -0,0 +1,141 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/fasta-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/threads, write to a buffer, add fn fasta_bench and
+// the input of reverse_complement and k_nucleotide
+
+use std::sync::OnceLock;
+
+use crate::checksum;
+
+const LINE_LENGTH: usize = 60;
+const IM: u32 = 139_968;
+const IA: u32 = 3877;
+const IC: u32 = 29_573;
+
+/// Length given to `fasta` for the input of `reverse_complement` and
+/// `k_nucleotide`.
+pub const INPUT_LENGTH: usize = 10_000;
+
+const ALU: &[u8] = b"GGCCGGGCGCGGTGGCTCACGCCTGTAATCCCAGCACTTTGG\
+GAGGCCGAGGCGGGCGGATCACCTGAGGTCAGGAGTTCGAGACCAGCCTGGCCAACATGGTGAAACCCCGTCTCTACT\
+AAAAATACAAAAATTAGCCGGGCGTGGTGGCGCGCGCCTGTAATCCCAGCTACTCGGGAGGCTGAGGCAGGAGAATC\
+GCTTGAACCCGGGAGGCGGAGGTTGCAGTGAGCCGAGATCGCGCCACTGCACTCCAGCCTGGGCGACAGAGCGAGAC\
+TCCGTCTCAAAAA";
+
+const IUB: &[(u8, f32)] = &[
+    (b'a', 0.27),
+    (b'c', 0.12),
+    (b'g', 0.12),
+    (b't', 0.27),
+    (b'B', 0.02),
+    (b'D', 0.02),
+    (b'H', 0.02),
+    (b'K', 0.02),
+    (b'M', 0.02),
+    (b'N', 0.02),
+    (b'R', 0.02),
+    (b'S', 0.02),
+    (b'V', 0.02),
+    (b'W', 0.02),
+    (b'Y', 0.02),
+];
+
+const HOMO_SAPIENS: &[(u8, f32)] = &[
+    (b'a', 0.302_954_94),
+    (b'c', 0.197_988_67),
+    (b'g', 0.197_547_42),
+    (b't', 0.301_509_53),
+];
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Random {
+        Random { last: 42 }
+    }
+
+    fn next(&mut self) -> f32 {
+        self.last = (self.last * IA + IC) % IM;
+        self.last as f32 / IM as f32
+    }
+}
+
+fn repeat(out: &mut Vec<u8>, header: &str, sequence: &[u8], n: usize) {
+    out.extend_from_slice(header.as_bytes());
+    let mut cycle = sequence.iter().cycle();
+    let mut left = n;
+    while left > 0 {
+        let line = left.min(LINE_LENGTH);
+        out.extend(cycle.by_ref().take(line));
+        out.push(b'\n');
+        left -= line;
+    }
+}
+
+fn random(out: &mut Vec<u8>, header: &str, table: &[(u8, f32)], n: usize, rng: &mut Random) {
+    out.extend_from_slice(header.as_bytes());
+    let mut cumulative = Vec::with_capacity(table.len());
+    let mut probability = 0.0;
+    for &(c, p) in table {
+        probability += p;
+        cumulative.push((c, probability));
+    }
+    let mut left = n;
+    while left > 0 {
+        let line = left.min(LINE_LENGTH);
+        for _ in 0..line {
+            let r = rng.next();
+            let c = cumulative
+                .iter()
+                .find(|&&(_, p)| r < p)
+                .unwrap_or(&cumulative[cumulative.len() - 1])
+                .0;
+            out.push(c);
+        }
+        out.push(b'\n');
+        left -= line;
+    }
+}
+
+/// Generates the three DNA sequences of length `2n`, `3n` and `5n` in FASTA
+/// format.
+pub fn fasta(n: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(n * 10 * 61 / 60 + 128);
+    let mut rng = Random::new();
+    repeat(&mut out, ">ONE Homo sapiens alu\n", ALU, n * 2);
+    random(&mut out, ">TWO IUB ambiguity codes\n", IUB, n * 3, &mut rng);
+    random(
+        &mut out,
+        ">THREE Homo sapiens frequency\n",
+        HOMO_SAPIENS,
+        n * 5,
+        &mut rng,
+    );
+    out
+}
+
+static INPUT: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// The output of `fasta(INPUT_LENGTH)`, generated on first use.
+pub fn input() -> &'static [u8] {
+    INPUT.get_or_init(|| fasta(INPUT_LENGTH))
+}
+
+#[no_mangle]
+pub extern "C" fn fasta_bench(n: i32) -> i32 {
+    checksum(&fasta(n as usize))
+}
+
+/// Generates the input of `reverse_complement_bench` and `k_nucleotide_bench`
+/// ahead of them.
+#[no_mangle]
+pub extern "C" fn fasta_init() {
+    input();
+}
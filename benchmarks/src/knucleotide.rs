@@ -0,0 +1,79 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/knucleotide-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/threads, use std's HashMap, read from and write to
+// buffers, add fn k_nucleotide_bench
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{checksum, fasta};
+
+const OCCURRENCES: &[&str] = &[
+    "GGT",
+    "GGTA",
+    "GGTATT",
+    "GGTATTTTAATT",
+    "GGTATTTTAATTTATAGT",
+];
+
+/// The third sequence of the FASTA `input`, upper-cased.
+fn three(input: &[u8]) -> Vec<u8> {
+    input
+        .split(|&c| c == b'\n')
+        .skip_while(|line| !line.starts_with(b">THREE"))
+        .skip(1)
+        .take_while(|line| line.first() != Some(&b'>'))
+        .flat_map(|line| line.iter().map(u8::to_ascii_uppercase))
+        .collect()
+}
+
+fn count(sequence: &[u8], k: usize) -> HashMap<&[u8], usize> {
+    let mut counts = HashMap::new();
+    for window in sequence.windows(k) {
+        *counts.entry(window).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn frequencies(out: &mut String, sequence: &[u8], k: usize) {
+    let mut counts: Vec<_> = count(sequence, k).into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let total = (sequence.len() + 1 - k) as f32;
+    for (nucleotides, count) in counts {
+        writeln!(
+            out,
+            "{} {:.3}",
+            String::from_utf8_lossy(nucleotides),
+            count as f32 * 100.0 / total
+        )
+        .unwrap();
+    }
+    out.push('\n');
+}
+
+/// Counts the nucleotide sequences of the third sequence of the FASTA
+/// `input`.
+pub fn k_nucleotide(input: &[u8]) -> String {
+    let sequence = three(input);
+    let mut out = String::new();
+    frequencies(&mut out, &sequence, 1);
+    frequencies(&mut out, &sequence, 2);
+    for occurrence in OCCURRENCES {
+        let count = count(&sequence, occurrence.len())
+            .get(occurrence.as_bytes())
+            .cloned()
+            .unwrap_or(0);
+        writeln!(out, "{}\t{}", count, occurrence).unwrap();
+    }
+    out
+}
+
+#[no_mangle]
+pub extern "C" fn k_nucleotide_bench() -> i32 {
+    checksum(k_nucleotide(fasta::input()).as_bytes())
+}
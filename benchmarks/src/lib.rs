@@ -1,12 +1,37 @@
 #![feature(core_intrinsics)]
 
+mod allocation;
+mod binarytrees;
+mod checksum;
 mod fannkuchredux;
+mod fasta;
 mod fib;
+mod knucleotide;
+mod mandelbrot;
 pub mod nbody;
+mod revcomp;
 mod sha;
+pub mod simd;
+mod spectralnorm;
 mod sum;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod threads;
+#[cfg(any(target_os = "wasi", not(target_arch = "wasm32")))]
+pub mod wasi;
 
+pub use allocation::*;
+pub use binarytrees::*;
+pub use checksum::*;
 pub use fannkuchredux::*;
+pub use fasta::*;
 pub use fib::*;
+pub use knucleotide::*;
+pub use mandelbrot::*;
+pub use revcomp::*;
 pub use sha::*;
+pub use spectralnorm::*;
 pub use sum::*;
+
+#[cfg(feature = "wee_alloc")]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
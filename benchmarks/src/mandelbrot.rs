@@ -0,0 +1,56 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/mandelbrot-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/threads/SIMD, write to a buffer, add fn mandelbrot_bench
+
+use crate::checksum;
+
+const ITER: usize = 50;
+const LIMIT: f64 = 2.0;
+
+/// Renders the Mandelbrot set as an `n` by `n` PBM bitmap.
+pub fn mandelbrot(n: usize) -> Vec<u8> {
+    let mut out = format!("P4\n{} {}\n", n, n).into_bytes();
+    let inv = 2.0 / n as f64;
+    for y in 0..n {
+        let ci = y as f64 * inv - 1.0;
+        let mut byte = 0u8;
+        let mut bit = 0;
+        for x in 0..n {
+            let cr = x as f64 * inv - 1.5;
+            let (mut zr, mut zi) = (0.0f64, 0.0f64);
+            let (mut tr, mut ti) = (0.0f64, 0.0f64);
+            let mut i = 0;
+            while i < ITER && tr + ti <= LIMIT * LIMIT {
+                zi = 2.0 * zr * zi + ci;
+                zr = tr - ti + cr;
+                tr = zr * zr;
+                ti = zi * zi;
+                i += 1;
+            }
+            byte <<= 1;
+            if tr + ti <= LIMIT * LIMIT {
+                byte |= 1;
+            }
+            bit += 1;
+            if bit == 8 {
+                out.push(byte);
+                byte = 0;
+                bit = 0;
+            }
+        }
+        if bit > 0 {
+            out.push(byte << (8 - bit));
+        }
+    }
+    out
+}
+
+#[no_mangle]
+pub extern "C" fn mandelbrot_bench(n: i32) -> i32 {
+    checksum(&mandelbrot(n as usize))
+}
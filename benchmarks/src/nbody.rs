@@ -19,11 +19,12 @@ fn sqrt(x: f64) -> f64 {
 }
 
 const PI: f64 = 3.141592653589793;
-const SOLAR_MASS: f64 = 4.0 * PI * PI;
+pub(crate) const SOLAR_MASS: f64 = 4.0 * PI * PI;
 const YEAR: f64 = 365.24;
-const N_BODIES: usize = 5;
+pub(crate) const N_BODIES: usize = 5;
 
-static mut BODIES: [Planet; N_BODIES] = [
+/// The bodies before the momentum of the system is offset.
+pub(crate) const INITIAL_BODIES: [Planet; N_BODIES] = [
     // Sun
     Planet {
         x: 0.0,
@@ -76,15 +77,17 @@ static mut BODIES: [Planet; N_BODIES] = [
     },
 ];
 
+static mut BODIES: [Planet; N_BODIES] = INITIAL_BODIES;
+
 #[derive(Clone, Copy)]
-struct Planet {
-    x: f64,
-    y: f64,
-    z: f64,
-    vx: f64,
-    vy: f64,
-    vz: f64,
-    mass: f64,
+pub(crate) struct Planet {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) z: f64,
+    pub(crate) vx: f64,
+    pub(crate) vy: f64,
+    pub(crate) vz: f64,
+    pub(crate) mass: f64,
 }
 
 fn advance(bodies: &mut [Planet; N_BODIES], dt: f64) {
@@ -0,0 +1,72 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/revcomp-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/threads, read from and write to buffers, add fn
+// reverse_complement_bench
+
+use crate::{checksum, fasta};
+
+const LINE_LENGTH: usize = 60;
+
+fn complement(c: u8) -> u8 {
+    match c {
+        b'A' | b'a' => b'T',
+        b'C' | b'c' => b'G',
+        b'G' | b'g' => b'C',
+        b'T' | b't' => b'A',
+        b'U' | b'u' => b'A',
+        b'M' | b'm' => b'K',
+        b'R' | b'r' => b'Y',
+        b'W' | b'w' => b'W',
+        b'S' | b's' => b'S',
+        b'Y' | b'y' => b'R',
+        b'K' | b'k' => b'M',
+        b'V' | b'v' => b'B',
+        b'H' | b'h' => b'D',
+        b'D' | b'd' => b'H',
+        b'B' | b'b' => b'V',
+        b'N' | b'n' => b'N',
+        c => c,
+    }
+}
+
+fn write_sequence(out: &mut Vec<u8>, sequence: &[u8]) {
+    let start = out.len();
+    out.extend(sequence.iter().rev().map(|&c| complement(c)));
+    let reversed = out.split_off(start);
+    for line in reversed.chunks(LINE_LENGTH) {
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+}
+
+/// Reverse-complements every sequence of the FASTA `input`.
+pub fn reverse_complement(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut sequence = Vec::new();
+    for line in input.split(|&c| c == b'\n') {
+        if line.first() == Some(&b'>') {
+            if !sequence.is_empty() {
+                write_sequence(&mut out, &sequence);
+                sequence.clear();
+            }
+            out.extend_from_slice(line);
+            out.push(b'\n');
+        } else {
+            sequence.extend_from_slice(line);
+        }
+    }
+    if !sequence.is_empty() {
+        write_sequence(&mut out, &sequence);
+    }
+    out
+}
+
+#[no_mangle]
+pub extern "C" fn reverse_complement_bench() -> i32 {
+    checksum(&reverse_complement(fasta::input()))
+}
@@ -0,0 +1,243 @@
+//! Kernels using 128-bit SIMD when built with `-C target-feature=+simd128`,
+//! and the same computation on scalars otherwise (natively, and in the
+//! default wasm build).
+
+use std::cell::RefCell;
+
+#[cfg(target_feature = "simd128")]
+use core::arch::wasm32::*;
+
+use crate::nbody::{INITIAL_BODIES, N_BODIES, SOLAR_MASS};
+
+const N_PAIRS: usize = N_BODIES * (N_BODIES - 1) / 2;
+
+const VECTOR_LENGTH: usize = 4096;
+const HAYSTACK_LENGTH: usize = 64 * 1024;
+
+#[derive(Clone, Copy)]
+pub struct Body {
+    position: [f64; 3],
+    velocity: [f64; 3],
+    mass: f64,
+}
+
+/// Bodies of `nbody` in the units of the simulation, with the momentum of the
+/// system offset.
+pub fn bodies() -> [Body; N_BODIES] {
+    let mut bodies = INITIAL_BODIES.map(|planet| Body {
+        position: [planet.x, planet.y, planet.z],
+        velocity: [planet.vx, planet.vy, planet.vz],
+        mass: planet.mass,
+    });
+    let mut momentum = [0.0; 3];
+    for body in bodies.iter() {
+        for (p, v) in momentum.iter_mut().zip(&body.velocity) {
+            *p += v * body.mass;
+        }
+    }
+    for (v, p) in bodies[0].velocity.iter_mut().zip(&momentum) {
+        *v = -p / SOLAR_MASS;
+    }
+    bodies
+}
+
+/// `dt / d³` for the distances `d` between the bodies of every pair, two
+/// pairs at a time.
+#[cfg(target_feature = "simd128")]
+fn magnitudes(deltas: &[[f64; 3]; N_PAIRS], dt: f64, magnitudes: &mut [f64; N_PAIRS]) {
+    let dt = f64x2_splat(dt);
+    for k in (0..N_PAIRS).step_by(2) {
+        let (a, b) = (deltas[k], deltas[k + 1]);
+        let dx = f64x2(a[0], b[0]);
+        let dy = f64x2(a[1], b[1]);
+        let dz = f64x2(a[2], b[2]);
+        let d2 = f64x2_add(
+            f64x2_add(f64x2_mul(dx, dx), f64x2_mul(dy, dy)),
+            f64x2_mul(dz, dz),
+        );
+        let mag = f64x2_div(dt, f64x2_mul(d2, f64x2_sqrt(d2)));
+        magnitudes[k] = f64x2_extract_lane::<0>(mag);
+        magnitudes[k + 1] = f64x2_extract_lane::<1>(mag);
+    }
+}
+
+#[cfg(not(target_feature = "simd128"))]
+fn magnitudes(deltas: &[[f64; 3]; N_PAIRS], dt: f64, magnitudes: &mut [f64; N_PAIRS]) {
+    for (d, mag) in deltas.iter().zip(magnitudes.iter_mut()) {
+        let d2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+        *mag = dt / (d2 * d2.sqrt());
+    }
+}
+
+fn advance(bodies: &mut [Body; N_BODIES], dt: f64) {
+    let mut deltas = [[0.0; 3]; N_PAIRS];
+    let mut k = 0;
+    for i in 0..N_BODIES {
+        for j in i + 1..N_BODIES {
+            let (pi, pj) = (bodies[i].position, bodies[j].position);
+            deltas[k] = [pi[0] - pj[0], pi[1] - pj[1], pi[2] - pj[2]];
+            k += 1;
+        }
+    }
+
+    let mut mags = [0.0; N_PAIRS];
+    magnitudes(&deltas, dt, &mut mags);
+
+    k = 0;
+    for i in 0..N_BODIES {
+        for j in i + 1..N_BODIES {
+            let (mass_i, mass_j) = (bodies[i].mass, bodies[j].mass);
+            for (c, d) in deltas[k].iter().enumerate() {
+                bodies[i].velocity[c] -= d * mass_j * mags[k];
+                bodies[j].velocity[c] += d * mass_i * mags[k];
+            }
+            k += 1;
+        }
+    }
+
+    for body in bodies.iter_mut() {
+        for c in 0..3 {
+            body.position[c] += dt * body.velocity[c];
+        }
+    }
+}
+
+/// The total energy of the system.
+pub fn energy(bodies: &[Body; N_BODIES]) -> f64 {
+    let mut e = 0.0;
+    for (i, bi) in bodies.iter().enumerate() {
+        let v2: f64 = bi.velocity.iter().map(|v| v * v).sum();
+        e += 0.5 * bi.mass * v2;
+        for bj in &bodies[i + 1..] {
+            let d2: f64 = (0..3)
+                .map(|c| bi.position[c] - bj.position[c])
+                .map(|d| d * d)
+                .sum();
+            e -= bi.mass * bj.mass / d2.sqrt();
+        }
+    }
+    e
+}
+
+/// Advances the simulation `steps` times by 0.01.
+pub fn nbody(bodies: &mut [Body; N_BODIES], steps: i32) {
+    for _ in 0..steps {
+        advance(bodies, 0.01);
+    }
+}
+
+#[cfg(target_feature = "simd128")]
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut sum = f32x4_splat(0.0);
+    let (a_chunks, b_chunks) = (a.chunks_exact(4), b.chunks_exact(4));
+    let tail: f32 = a_chunks
+        .remainder()
+        .iter()
+        .zip(b_chunks.remainder())
+        .map(|(a, b)| a * b)
+        .sum();
+    for (a, b) in a_chunks.zip(b_chunks) {
+        let (a, b) = unsafe {
+            (
+                v128_load(a.as_ptr() as *const v128),
+                v128_load(b.as_ptr() as *const v128),
+            )
+        };
+        sum = f32x4_add(sum, f32x4_mul(a, b));
+    }
+    f32x4_extract_lane::<0>(sum)
+        + f32x4_extract_lane::<1>(sum)
+        + f32x4_extract_lane::<2>(sum)
+        + f32x4_extract_lane::<3>(sum)
+        + tail
+}
+
+#[cfg(not(target_feature = "simd128"))]
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// The index of the first `needle` in `haystack`.
+#[cfg(target_feature = "simd128")]
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    let needles = u8x16_splat(needle);
+    let chunks = haystack.chunks_exact(16);
+    let tail = chunks.remainder();
+    for (i, chunk) in chunks.enumerate() {
+        let bytes = unsafe { v128_load(chunk.as_ptr() as *const v128) };
+        let mask = u8x16_bitmask(u8x16_eq(bytes, needles));
+        if mask != 0 {
+            return Some(i * 16 + mask.trailing_zeros() as usize);
+        }
+    }
+    tail.iter()
+        .position(|&b| b == needle)
+        .map(|i| haystack.len() - tail.len() + i)
+}
+
+/// The index of the first `needle` in `haystack`.
+#[cfg(not(target_feature = "simd128"))]
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    haystack.iter().position(|&b| b == needle)
+}
+
+struct Data {
+    bodies: [Body; N_BODIES],
+    a: Vec<f32>,
+    b: Vec<f32>,
+    haystack: Vec<u8>,
+}
+
+impl Data {
+    fn new() -> Data {
+        Data {
+            bodies: bodies(),
+            a: (0..VECTOR_LENGTH).map(|i| i as f32 / 64.0).collect(),
+            b: (0..VECTOR_LENGTH).map(|i| 1.0 / (i + 1) as f32).collect(),
+            // Only the last byte matches.
+            haystack: (0..HAYSTACK_LENGTH)
+                .map(|i| {
+                    if i == HAYSTACK_LENGTH - 1 {
+                        0xff
+                    } else {
+                        (i % 251) as u8
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+thread_local! {
+    /// The state of the SIMD workloads, set up on first use.
+    static DATA: RefCell<Data> = RefCell::new(Data::new());
+}
+
+fn with_data<T>(f: impl FnOnce(&mut Data) -> T) -> T {
+    DATA.with(|data| f(&mut data.borrow_mut()))
+}
+
+/// Sets up the data of the other exports ahead of them.
+#[no_mangle]
+pub extern "C" fn simd_init() {
+    with_data(|_| ());
+}
+
+#[no_mangle]
+pub extern "C" fn simd_nbody_bench(steps: i32) {
+    with_data(|data| nbody(&mut data.bodies, steps));
+}
+
+#[no_mangle]
+pub extern "C" fn simd_dot_bench(n: i32) -> f32 {
+    with_data(|data| (0..n).map(|_| dot(&data.a, &data.b)).sum())
+}
+
+#[no_mangle]
+pub extern "C" fn simd_find_bench(n: i32) -> i32 {
+    with_data(|data| {
+        (0..n)
+            .map(|_| find_byte(&data.haystack, 0xff).unwrap_or(0) as i32)
+            .fold(0, |a, b| a ^ b)
+    })
+}
@@ -0,0 +1,49 @@
+// Code adapted from https://benchmarksgame-team.pages.debian.net/benchmarksgame/program/spectralnorm-rust-1.html
+
+// The Computer Language Benchmarks Game
+// https://salsa.debian.org/benchmarksgame-team/benchmarksgame/
+//
+// contributed by the Rust Project Developers
+// contributed by TeXitoi
+// modified to remove main/threads, add fn spectral_norm_bench
+
+fn a(i: usize, j: usize) -> f64 {
+    ((i + j) * (i + j + 1) / 2 + i + 1) as f64
+}
+
+fn mult_av(v: &[f64], out: &mut [f64]) {
+    for (i, out) in out.iter_mut().enumerate() {
+        *out = v.iter().enumerate().map(|(j, v)| v / a(i, j)).sum();
+    }
+}
+
+fn mult_atv(v: &[f64], out: &mut [f64]) {
+    for (i, out) in out.iter_mut().enumerate() {
+        *out = v.iter().enumerate().map(|(j, v)| v / a(j, i)).sum();
+    }
+}
+
+fn mult_atav(v: &[f64], out: &mut [f64], tmp: &mut [f64]) {
+    mult_av(v, tmp);
+    mult_atv(tmp, out);
+}
+
+/// Approximates the spectral norm of the infinite matrix A, truncated to
+/// `n` by `n`.
+pub fn spectral_norm(n: usize) -> f64 {
+    let mut u = vec![1.0; n];
+    let mut v = vec![0.0; n];
+    let mut tmp = vec![0.0; n];
+    for _ in 0..10 {
+        mult_atav(&u, &mut v, &mut tmp);
+        mult_atav(&v, &mut u, &mut tmp);
+    }
+    let vbv: f64 = u.iter().zip(&v).map(|(u, v)| u * v).sum();
+    let vv: f64 = v.iter().map(|v| v * v).sum();
+    (vbv / vv).sqrt()
+}
+
+#[no_mangle]
+pub extern "C" fn spectral_norm_bench(n: i32) -> f64 {
+    spectral_norm(n as usize)
+}
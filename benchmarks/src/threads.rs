@@ -0,0 +1,39 @@
+//! The reduction of `wat/threads.wat`, natively.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+
+/// Number of integers summed.
+pub const LENGTH: usize = 262_144;
+
+static DATA: OnceLock<Vec<u32>> = OnceLock::new();
+
+/// The consecutive integers summed, generated on first use.
+pub fn data() -> &'static [u32] {
+    DATA.get_or_init(|| (0..LENGTH as u32).collect())
+}
+
+/// Sums `data()` on `threads` threads, each adding its share to the total
+/// atomically.
+pub fn parallel_sum(threads: usize) -> u64 {
+    let data = data();
+    let sum = AtomicU64::new(0);
+    let chunk = LENGTH / threads;
+    thread::scope(|scope| {
+        for thread in 0..threads {
+            let end = if thread == threads - 1 {
+                LENGTH
+            } else {
+                (thread + 1) * chunk
+            };
+            let share = &data[thread * chunk..end];
+            let sum = &sum;
+            scope.spawn(move || {
+                let share: u64 = share.iter().map(|&v| u64::from(v)).sum();
+                sum.fetch_add(share, Ordering::SeqCst);
+            });
+        }
+    });
+    sum.load(Ordering::SeqCst)
+}
@@ -0,0 +1,139 @@
+//! Syscall-heavy workloads, built for `wasm32-wasip1` and natively.
+//!
+//! Every function works in a directory set up by `init`. Under WASI it is the
+//! directory the host preopens as `/sandbox`.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime};
+
+/// Guest path of the preopened directory.
+pub const SANDBOX: &str = "/sandbox";
+
+const FILE_SIZE: usize = 64 * 1024;
+const LISTED_FILES: usize = 100;
+
+/// Creates the files the workloads read in `dir`.
+pub fn init(dir: &Path) -> io::Result<()> {
+    fs::write(dir.join("read.bin"), vec![0x5a; FILE_SIZE])?;
+    let list = dir.join("list");
+    fs::create_dir_all(&list)?;
+    for i in 0..LISTED_FILES {
+        fs::write(list.join(format!("{}.txt", i)), b"")?;
+    }
+    Ok(())
+}
+
+/// Writes a 64 KiB file `n` times, in 4 KiB chunks.
+pub fn file_write(dir: &Path, n: u32) -> io::Result<()> {
+    let chunk = [0xa5; 4096];
+    for _ in 0..n {
+        let mut file = fs::File::create(dir.join("write.bin"))?;
+        for _ in 0..FILE_SIZE / chunk.len() {
+            file.write_all(&chunk)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the 64 KiB file `n` times, in 4 KiB chunks.
+pub fn file_read(dir: &Path, n: u32) -> io::Result<usize> {
+    let mut chunk = [0; 4096];
+    let mut total = 0;
+    for _ in 0..n {
+        let mut file = fs::File::open(dir.join("read.bin"))?;
+        loop {
+            match file.read(&mut chunk)? {
+                0 => break,
+                read => total += read,
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Lists a directory of 100 files `n` times.
+pub fn list_dir(dir: &Path, n: u32) -> io::Result<usize> {
+    let mut entries = 0;
+    for _ in 0..n {
+        for entry in fs::read_dir(dir.join("list"))? {
+            entry?;
+            entries += 1;
+        }
+    }
+    Ok(entries)
+}
+
+/// Reads the monotonic and the wall clock `n` times.
+pub fn clock(n: u32) -> u64 {
+    let mut checksum = 0;
+    for _ in 0..n {
+        let monotonic = Instant::now();
+        let wall = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        checksum ^= wall.as_nanos() as u64 ^ monotonic.elapsed().as_nanos() as u64;
+    }
+    checksum
+}
+
+/// Gets 32 random bytes `n` times.
+pub fn random(n: u32) -> u8 {
+    let mut bytes = [0; 32];
+    let mut checksum = 0;
+    for _ in 0..n {
+        getrandom::getrandom(&mut bytes).expect("random bytes should be available");
+        checksum ^= bytes[0];
+    }
+    checksum
+}
+
+#[cfg(target_os = "wasi")]
+mod exports {
+    use std::path::Path;
+
+    use super::SANDBOX;
+
+    extern "C" {
+        /// Runs the static constructors, including the one of the C library
+        /// that registers the preopened directories. Modules without a
+        /// `_start` have to call it themselves.
+        fn __wasm_call_ctors();
+    }
+
+    fn sandbox() -> &'static Path {
+        Path::new(SANDBOX)
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_init() {
+        unsafe { __wasm_call_ctors() };
+        super::init(sandbox()).unwrap();
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_file_write(n: u32) {
+        super::file_write(sandbox(), n).unwrap();
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_file_read(n: u32) {
+        super::file_read(sandbox(), n).unwrap();
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_list_dir(n: u32) {
+        super::list_dir(sandbox(), n).unwrap();
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_clock(n: u32) {
+        super::clock(n);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wasi_random(n: u32) {
+        super::random(n);
+    }
+}
@@ -0,0 +1,24 @@
+;; Copying and filling 64 KiB with the bulk memory instructions. `mvp.wat`
+;; does the same with byte loops.
+(module
+  (memory 2)
+
+  ;; Copies the first 64 KiB over the next `n` times.
+  (func (export "memory_copy") (param $n i32) (result i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (memory.copy (i32.const 65536) (i32.const 0) (i32.const 65536))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.load8_u (i32.const 131071)))
+
+  ;; Fills the first 64 KiB `n` times.
+  (func (export "memory_fill") (param $n i32) (result i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (memory.fill (i32.const 0) (local.get $n) (i32.const 65536))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.load8_u (i32.const 65535))))
@@ -0,0 +1,21 @@
+;; Returning two values from a function with multi-value. `mvp.wat` returns
+;; the second one through memory instead.
+(module
+  (func $divmod (param $a i32) (param $b i32) (result i32 i32)
+    (i32.div_u (local.get $a) (local.get $b))
+    (i32.rem_u (local.get $a) (local.get $b)))
+
+  ;; Sums the quotients and remainders of `n` divisions.
+  (func (export "multi_value") (param $n i32) (result i32)
+    (local $sum i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (call $divmod (local.get $n) (i32.const 7))
+        i32.add
+        local.get $sum
+        i32.add
+        local.set $sum
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum)))
@@ -0,0 +1,95 @@
+;; The computations of `bulk_memory.wat`, `multi_value.wat` and
+;; `reference_types.wat` with MVP instructions only, as the baseline of the
+;; proposals.
+(module
+  (type $value (func (result i32)))
+
+  (memory 2)
+
+  (table funcref (elem $zero $one $two $three))
+
+  (func $zero (result i32) (i32.const 0))
+  (func $one (result i32) (i32.const 1))
+  (func $two (result i32) (i32.const 2))
+  (func $three (result i32) (i32.const 3))
+
+  ;; The 16 slots of `table_in_memory` hold function indices from 0 on.
+  (func $init
+    (local $i i32)
+    (loop $fill
+      (i32.store
+        (i32.shl (local.get $i) (i32.const 2))
+        (i32.and (local.get $i) (i32.const 3)))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $fill (i32.lt_u (local.get $i) (i32.const 16)))))
+  (start $init)
+
+  (func (export "memory_copy_loop") (param $n i32) (result i32)
+    (local $i i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $i (i32.const 0))
+        (loop $copy
+          (i32.store8
+            (i32.add (local.get $i) (i32.const 65536))
+            (i32.load8_u (local.get $i)))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br_if $copy (i32.lt_u (local.get $i) (i32.const 65536))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.load8_u (i32.const 131071)))
+
+  (func (export "memory_fill_loop") (param $n i32) (result i32)
+    (local $i i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $i (i32.const 0))
+        (loop $fill
+          (i32.store8 (local.get $i) (local.get $n))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br_if $fill (i32.lt_u (local.get $i) (i32.const 65536))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.load8_u (i32.const 65535)))
+
+  ;; Returns the quotient and stores the remainder at `out`.
+  (func $divmod (param $a i32) (param $b i32) (param $out i32) (result i32)
+    (i32.store (local.get $out) (i32.rem_u (local.get $a) (local.get $b)))
+    (i32.div_u (local.get $a) (local.get $b)))
+
+  (func (export "out_pointer") (param $n i32) (result i32)
+    (local $sum i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $sum
+          (i32.add
+            (local.get $sum)
+            (i32.add
+              (call $divmod (local.get $n) (i32.const 7) (i32.const 64))
+              (i32.load (i32.const 64)))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum))
+
+  (func (export "table_in_memory") (param $n i32) (result i32)
+    (local $slot i32) (local $sum i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $slot (i32.shl (i32.and (local.get $n) (i32.const 15)) (i32.const 2)))
+        (i32.store
+          (local.get $slot)
+          (i32.load
+            (i32.shl
+              (i32.and (i32.mul (local.get $n) (i32.const 7)) (i32.const 15))
+              (i32.const 2))))
+        (local.set $sum
+          (i32.add
+            (local.get $sum)
+            (call_indirect (type $value) (i32.load (local.get $slot)))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum)))
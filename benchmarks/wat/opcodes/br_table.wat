@@ -0,0 +1,39 @@
+;; `br_table` over eight cases, picked by a pseudo-random sequence so the
+;; branch cannot be predicted.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $state i32) (local $sum i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $state
+          (i32.add (i32.mul (local.get $state) (i32.const 1103515245)) (i32.const 12345)))
+        (block $next
+          (block $7
+            (block $6
+              (block $5
+                (block $4
+                  (block $3
+                    (block $2
+                      (block $1
+                        (block $0
+                          (br_table $0 $1 $2 $3 $4 $5 $6 $7
+                            (i32.shr_u (local.get $state) (i32.const 29))))
+                        (local.set $sum (i32.add (local.get $sum) (i32.const 1)))
+                        (br $next))
+                      (local.set $sum (i32.sub (local.get $sum) (i32.const 3)))
+                      (br $next))
+                    (local.set $sum (i32.xor (local.get $sum) (i32.const 0x55)))
+                    (br $next))
+                  (local.set $sum (i32.shl (local.get $sum) (i32.const 1)))
+                  (br $next))
+                (local.set $sum (i32.shr_u (local.get $sum) (i32.const 1)))
+                (br $next))
+              (local.set $sum (i32.add (local.get $sum) (local.get $n)))
+              (br $next))
+            (local.set $sum (i32.mul (local.get $sum) (i32.const 3)))
+            (br $next))
+          (local.set $sum (i32.rotl (local.get $sum) (i32.const 5))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum)))
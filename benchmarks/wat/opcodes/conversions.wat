@@ -0,0 +1,30 @@
+;; Conversions between integers and floats, integer widths and float widths.
+;; The values converted to integers are in range, so nothing traps.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $i i32) (local $x f64) (local $y f32) (local $wide i64)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $x
+          (f64.add
+            (f64.convert_i32_u (local.get $n))
+            (f64.convert_i64_s (i64.extend_i32_s (local.get $i)))))
+        (local.set $y (f32.demote_f64 (f64.mul (local.get $x) (f64.const 0.25))))
+        (local.set $wide
+          (i64.add
+            (local.get $wide)
+            (i64.trunc_f64_s (f64.promote_f32 (local.get $y)))))
+        (local.set $i
+          (i32.and
+            (i32.add
+              (i32.trunc_f32_u (local.get $y))
+              (i32.wrap_i64 (local.get $wide)))
+            (i32.const 0xffff)))
+        (local.set $i
+          (i32.xor
+            (local.get $i)
+            (i32.shr_u (i32.reinterpret_f32 (f32.convert_i32_s (local.get $i))) (i32.const 20))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.xor (local.get $i) (i32.wrap_i64 (local.get $wide)))))
@@ -0,0 +1,22 @@
+;; f32 arithmetic, square roots, minimums and maximums. The values stay
+;; positive and bounded, so every runtime computes the same bits.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $x f32) (local $y f32)
+    (local.set $x (f32.const 1))
+    (local.set $y (f32.const 2))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $x
+          (f32.add
+            (f32.mul (local.get $x) (f32.const 0.5))
+            (f32.div (local.get $y) (f32.add (local.get $x) (f32.const 1)))))
+        (local.set $y
+          (f32.sqrt
+            (f32.add (local.get $y) (f32.abs (f32.sub (local.get $x) (local.get $y))))))
+        (local.set $x (f32.min (f32.max (local.get $x) (f32.const 0.25)) (f32.const 1000)))
+        (local.set $y (f32.add (local.get $y) (f32.neg (f32.mul (local.get $x) (f32.const 0.001)))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.reinterpret_f32 (f32.add (local.get $x) (local.get $y)))))
@@ -0,0 +1,37 @@
+;; f64 arithmetic, square roots, rounding, minimums and maximums. The values
+;; stay positive and bounded, so every runtime computes the same bits. Every
+;; iteration adds an input derived from the counter, so the loop does not
+;; settle on a fixed point, and the result sums `x` over all of them.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $x f64) (local $y f64) (local $sum f64) (local $bits i64)
+    (local.set $x (f64.const 1))
+    (local.set $y (f64.const 2))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $x
+          (f64.add
+            (f64.mul (local.get $x) (f64.const 0.5))
+            (f64.div
+              (f64.add
+                (local.get $y)
+                (f64.mul (f64.convert_i32_u (local.get $n)) (f64.const 0.0001)))
+              (f64.add (local.get $x) (f64.const 1)))))
+        (local.set $y
+          (f64.sqrt
+            (f64.add (local.get $y) (f64.abs (f64.sub (local.get $x) (local.get $y))))))
+        (local.set $x (f64.min (f64.max (local.get $x) (f64.const 0.25)) (f64.const 1000)))
+        (local.set $y
+          (f64.add
+            (local.get $y)
+            (f64.mul
+              (f64.sub (f64.nearest (local.get $x)) (f64.floor (local.get $x)))
+              (f64.const 0.001))))
+        (local.set $sum (f64.add (local.get $sum) (local.get $x)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    ;; The high half holds the sign, the exponent and the top of the mantissa.
+    (local.set $bits (i64.reinterpret_f64 (f64.add (local.get $sum) (local.get $y))))
+    (i32.wrap_i64
+      (i64.xor (local.get $bits) (i64.shr_u (local.get $bits) (i64.const 32))))))
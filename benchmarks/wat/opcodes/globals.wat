@@ -0,0 +1,19 @@
+;; Reading and writing mutable globals, of both integer widths.
+(module
+  (global $count (mut i32) (i32.const 0))
+  (global $a (mut i32) (i32.const 1))
+  (global $b (mut i32) (i32.const 2))
+  (global $total (mut i64) (i64.const 0))
+
+  (func (export "run") (param $n i32) (result i32)
+    (global.set $count (local.get $n))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (global.get $count)))
+        (global.set $a (i32.add (global.get $a) (global.get $b)))
+        (global.set $b (i32.xor (global.get $b) (global.get $a)))
+        (global.set $total
+          (i64.add (global.get $total) (i64.extend_i32_u (global.get $a))))
+        (global.set $count (i32.sub (global.get $count) (i32.const 1)))
+        (br $repeat)))
+    (i32.xor (global.get $b) (i32.wrap_i64 (global.get $total)))))
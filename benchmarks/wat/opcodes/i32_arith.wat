@@ -0,0 +1,21 @@
+;; i32 arithmetic and bitwise instructions on a dependency chain.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $a i32) (local $b i32)
+    (local.set $a (i32.const 1))
+    (local.set $b (i32.const 0x9e3779b9))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $a (i32.add (i32.mul (local.get $a) (i32.const 1103515245)) (local.get $n)))
+        (local.set $b (i32.xor (local.get $b) (i32.rotl (local.get $a) (i32.const 7))))
+        (local.set $a (i32.sub (local.get $a) (i32.shr_u (local.get $b) (i32.const 3))))
+        (local.set $b (i32.or (i32.and (local.get $b) (local.get $a)) (i32.shl (local.get $n) (i32.const 5))))
+        (local.set $a
+          (i32.add
+            (local.get $a)
+            (i32.div_u (local.get $b) (i32.or (local.get $n) (i32.const 1)))))
+        (local.set $b (i32.add (local.get $b) (i32.rem_s (local.get $a) (i32.const 1009))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.xor (local.get $a) (local.get $b))))
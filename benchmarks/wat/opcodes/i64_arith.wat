@@ -0,0 +1,22 @@
+;; i64 arithmetic and bitwise instructions on a dependency chain.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $i i64) (local $a i64) (local $b i64)
+    (local.set $i (i64.extend_i32_u (local.get $n)))
+    (local.set $a (i64.const 1))
+    (local.set $b (i64.const 0x9e3779b97f4a7c15))
+    (block $done
+      (loop $repeat
+        (br_if $done (i64.eqz (local.get $i)))
+        (local.set $a (i64.add (i64.mul (local.get $a) (i64.const 6364136223846793005)) (local.get $i)))
+        (local.set $b (i64.xor (local.get $b) (i64.rotl (local.get $a) (i64.const 23))))
+        (local.set $a (i64.sub (local.get $a) (i64.shr_u (local.get $b) (i64.const 11))))
+        (local.set $b (i64.or (i64.and (local.get $b) (local.get $a)) (i64.shl (local.get $i) (i64.const 17))))
+        (local.set $a
+          (i64.add
+            (local.get $a)
+            (i64.div_u (local.get $b) (i64.or (local.get $i) (i64.const 1)))))
+        (local.set $b (i64.add (local.get $b) (i64.rem_s (local.get $a) (i64.const 1000003))))
+        (local.set $i (i64.sub (local.get $i) (i64.const 1)))
+        (br $repeat)))
+    (i32.wrap_i64 (i64.xor (local.get $a) (i64.shr_u (local.get $b) (i64.const 32))))))
@@ -0,0 +1,33 @@
+;; Loads and stores of every width, at addresses depending on the previous
+;; load.
+(module
+  (memory 1)
+
+  (func (export "run") (param $n i32) (result i32)
+    (local $address i32) (local $value i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        ;; 8-byte aligned addresses in the first 32 KiB.
+        (local.set $address
+          (i32.and
+            (i32.add (local.get $value) (i32.mul (local.get $n) (i32.const 72)))
+            (i32.const 0x7ff8)))
+        (local.set $value (i32.add (i32.load (local.get $address)) (local.get $n)))
+        (i32.store offset=4 (local.get $address) (local.get $value))
+        (i64.store offset=32768
+          (local.get $address)
+          (i64.add
+            (i64.load offset=32768 (local.get $address))
+            (i64.extend_i32_u (local.get $value))))
+        (i32.store8 (local.get $address) (local.get $n))
+        (i32.store16 offset=2 (local.get $address) (local.get $value))
+        (local.set $value
+          (i32.add
+            (local.get $value)
+            (i32.add
+              (i32.load8_u offset=5 (local.get $address))
+              (i32.load16_s offset=6 (local.get $address)))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $value)))
@@ -0,0 +1,35 @@
+;; Rotating values through more locals than there are registers.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $l0 i32) (local $l1 i32) (local $l2 i32) (local $l3 i32)
+    (local $l4 i32) (local $l5 i32) (local $l6 i32) (local $l7 i32)
+    (local $l8 i32) (local $l9 i32) (local $l10 i32) (local $l11 i32)
+    (local $l12 i32) (local $l13 i32) (local $l14 i32) (local $l15 i32)
+    (local $l16 i32) (local $l17 i32) (local $l18 i32) (local $l19 i32)
+    (local.set $l0 (i32.const 1))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $l19
+          (i32.add (local.get $l18) (local.tee $l18 (i32.add (local.get $l17) (local.get $n)))))
+        (local.set $l17 (i32.add (local.get $l16) (local.get $l19)))
+        (local.set $l16 (local.get $l15))
+        (local.set $l15 (i32.add (local.get $l14) (local.get $l17)))
+        (local.set $l14 (local.get $l13))
+        (local.set $l13 (i32.add (local.get $l12) (local.get $l15)))
+        (local.set $l12 (local.get $l11))
+        (local.set $l11 (i32.add (local.get $l10) (local.get $l13)))
+        (local.set $l10 (local.get $l9))
+        (local.set $l9 (i32.add (local.get $l8) (local.get $l11)))
+        (local.set $l8 (local.get $l7))
+        (local.set $l7 (i32.add (local.get $l6) (local.get $l9)))
+        (local.set $l6 (local.get $l5))
+        (local.set $l5 (i32.add (local.get $l4) (local.get $l7)))
+        (local.set $l4 (local.get $l3))
+        (local.set $l3 (i32.add (local.get $l2) (local.get $l5)))
+        (local.set $l2 (local.get $l1))
+        (local.set $l1 (i32.add (local.get $l0) (local.get $l3)))
+        (local.set $l0 (i32.xor (local.get $l19) (local.get $l1)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (i32.add (local.get $l0) (local.get $l19))))
@@ -0,0 +1,37 @@
+;; `select` on conditions from a pseudo-random sequence, as branchless
+;; minimums, maximums and absolute values.
+(module
+  (func (export "run") (param $n i32) (result i32)
+    (local $state i32) (local $low i32) (local $high i32) (local $sum i32)
+    (local.set $low (i32.const 0x7fffffff))
+    (local.set $high (i32.const 0x80000000))
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $state
+          (i32.add (i32.mul (local.get $state) (i32.const 1103515245)) (i32.const 12345)))
+        (local.set $low
+          (select
+            (local.get $state)
+            (local.get $low)
+            (i32.lt_s (local.get $state) (local.get $low))))
+        (local.set $high
+          (select
+            (local.get $state)
+            (local.get $high)
+            (i32.gt_s (local.get $state) (local.get $high))))
+        (local.set $sum
+          (i32.add
+            (local.get $sum)
+            (select
+              (i32.sub (i32.const 0) (local.get $state))
+              (local.get $state)
+              (i32.lt_s (local.get $state) (i32.const 0)))))
+        (local.set $sum
+          (select
+            (i32.add (local.get $sum) (local.get $low))
+            (i32.sub (local.get $sum) (local.get $high))
+            (i32.and (local.get $state) (i32.const 0x10000))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum)))
@@ -0,0 +1,40 @@
+;; Shuffling function references in a table with `table.get` and `table.set`,
+;; and calling them. `mvp.wat` keeps function indices in memory instead.
+(module
+  (type $value (func (result i32)))
+
+  (table $functions funcref (elem $zero $one $two $three))
+  (table $slots 16 funcref)
+
+  (func $zero (result i32) (i32.const 0))
+  (func $one (result i32) (i32.const 1))
+  (func $two (result i32) (i32.const 2))
+  (func $three (result i32) (i32.const 3))
+
+  (func $init
+    (local $i i32)
+    (loop $fill
+      (table.set $slots
+        (local.get $i)
+        (table.get $functions (i32.and (local.get $i) (i32.const 3))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $fill (i32.lt_u (local.get $i) (i32.const 16)))))
+  (start $init)
+
+  ;; Moves a reference between slots and calls it, `n` times.
+  (func (export "table_get_set") (param $n i32) (result i32)
+    (local $slot i32) (local $sum i32)
+    (block $done
+      (loop $repeat
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $slot (i32.and (local.get $n) (i32.const 15)))
+        (table.set $slots
+          (local.get $slot)
+          (table.get $slots (i32.and (i32.mul (local.get $n) (i32.const 7)) (i32.const 15))))
+        (local.set $sum
+          (i32.add
+            (local.get $sum)
+            (call_indirect $slots (type $value) (local.get $slot))))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $repeat)))
+    (local.get $sum)))
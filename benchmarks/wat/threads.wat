@@ -0,0 +1,66 @@
+;; Parallel reduction over a shared memory, for the `threads` workload.
+;;
+;; The host instantiates the module once per thread over the same memory and
+;; calls `init` once. For every run it calls `reset` with the number of
+;; threads, `work` on every thread and `wait`, which blocks until they are all
+;; done and returns the sum, or -1 if one of them is not done after a second.
+(module
+  (import "env" "memory" (memory 17 17 shared))
+
+  ;; The sum is at 0, the number of running threads at 8 and the data, 1 MiB
+  ;; of consecutive integers, from 64 on.
+  (global $data i32 (i32.const 64))
+  (global $length i32 (i32.const 262144))
+
+  (func (export "init")
+    (local $i i32)
+    (loop $fill
+      (i32.store
+        (i32.add (global.get $data) (i32.shl (local.get $i) (i32.const 2)))
+        (local.get $i))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $fill (i32.lt_u (local.get $i) (global.get $length)))))
+
+  (func (export "reset") (param $threads i32)
+    (i64.atomic.store (i32.const 0) (i64.const 0))
+    (i32.atomic.store (i32.const 8) (local.get $threads)))
+
+  ;; Adds this thread's share of the data to the sum, and wakes up the waiting
+  ;; thread if it is the last one to finish.
+  (func (export "work") (param $thread i32) (param $threads i32)
+    (local $chunk i32) (local $i i32) (local $end i32) (local $sum i64)
+    (local.set $chunk (i32.div_u (global.get $length) (local.get $threads)))
+    (local.set $i (i32.mul (local.get $thread) (local.get $chunk)))
+    (local.set $end
+      (select
+        (global.get $length)
+        (i32.add (local.get $i) (local.get $chunk))
+        (i32.eq (local.get $thread) (i32.sub (local.get $threads) (i32.const 1)))))
+    (block $done
+      (loop $add
+        (br_if $done (i32.ge_u (local.get $i) (local.get $end)))
+        (local.set $sum
+          (i64.add
+            (local.get $sum)
+            (i64.extend_i32_u
+              (i32.load
+                (i32.add (global.get $data) (i32.shl (local.get $i) (i32.const 2)))))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $add)))
+    (drop (i64.atomic.rmw.add (i32.const 0) (local.get $sum)))
+    (if (i32.eq (i32.atomic.rmw.sub (i32.const 8) (i32.const 1)) (i32.const 1))
+      (then (drop (memory.atomic.notify (i32.const 8) (i32.const 1))))))
+
+  (func (export "wait") (result i64)
+    (local $running i32)
+    (block $done
+      (loop $wait
+        (local.set $running (i32.atomic.load (i32.const 8)))
+        (br_if $done (i32.eqz (local.get $running)))
+        ;; 2 is a timeout.
+        (if (i32.eq
+              (memory.atomic.wait32 (i32.const 8) (local.get $running) (i64.const 1000000000))
+              (i32.const 2))
+          (then (return (i64.const -1))))
+        (br $wait)))
+    (i64.atomic.load (i32.const 0))))
@@ -0,0 +1,126 @@
+//! Builds the `benchmarks` crate to wasm, so `src/lib.rs` can embed it.
+//!
+//! The module is built into `benchmarks/target/wasm32-unknown-unknown/release`,
+//! where `make build` used to put it. Set `WASM_BENCH_WASM` to the path of a
+//! prebuilt module to skip the build.
+//!
+//! With the `wasi` feature the crate is also built for `wasm32-wasip1`, or
+//! taken from `WASM_BENCH_WASI_WASM`. With the `wee-alloc` feature it is built
+//! a second time with `wee_alloc` as its allocator, or taken from
+//! `WASM_BENCH_WEE_ALLOC_WASM`. With the `simd` feature it is built with
+//! `-C target-feature=+simd128`, or taken from `WASM_BENCH_SIMD_WASM`.
+//!
+//! The hand-written modules in `benchmarks/wat` are assembled into `OUT_DIR`.
+//! Those of `benchmarks/wat/opcodes` are listed in `OUT_DIR/opcodes.rs`, so a
+//! new file there is a new workload.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[path = "build/wasm.rs"]
+mod wasm;
+
+use wasm::{emit, fail, Build, UNKNOWN};
+
+const WASI: Build = Build {
+    target: "wasm32-wasip1",
+    prebuilt_var: "WASM_BENCH_WASI_WASM",
+    output_var: "WASM_BENCH_BENCHMARKS_WASI_WASM",
+    variant: None,
+    features: &[],
+    rustflags: "",
+};
+
+const WEE_ALLOC: Build = Build {
+    target: "wasm32-unknown-unknown",
+    prebuilt_var: "WASM_BENCH_WEE_ALLOC_WASM",
+    output_var: "WASM_BENCH_BENCHMARKS_WEE_ALLOC_WASM",
+    variant: Some("wee_alloc"),
+    features: &["wee_alloc"],
+    rustflags: "",
+};
+
+const SIMD: Build = Build {
+    target: "wasm32-unknown-unknown",
+    prebuilt_var: "WASM_BENCH_SIMD_WASM",
+    output_var: "WASM_BENCH_BENCHMARKS_SIMD_WASM",
+    variant: Some("simd"),
+    features: &[],
+    rustflags: "-C target-feature=+simd128",
+};
+
+/// The modules in `benchmarks/wat`.
+const WAT_MODULES: &[&str] = &[
+    "bulk_memory",
+    "multi_value",
+    "mvp",
+    "reference_types",
+    "threads",
+];
+
+/// Assembles `benchmarks/wat/<name>.wat` into `OUT_DIR/<name>.wasm`.
+fn assemble(benchmarks: &Path, name: &str) {
+    let wat = benchmarks.join("wat").join(format!("{}.wat", name));
+    println!("cargo:rerun-if-changed={}", wat.display());
+    let wasm = wat::parse_file(&wat).unwrap_or_else(|e| fail(&e.to_string()));
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join(format!("{}.wasm", name));
+    fs::create_dir_all(out.parent().unwrap())
+        .and_then(|()| fs::write(&out, wasm))
+        .unwrap_or_else(|e| fail(&format!("failed to write {}: {}", out.display(), e)));
+}
+
+/// Assembles every module of `benchmarks/wat/opcodes` and writes
+/// `OUT_DIR/opcodes.rs`, the array of their workload names, `op_<file name>`,
+/// and modules that `src/lib.rs` includes.
+fn assemble_opcodes(benchmarks: &Path) {
+    let dir = benchmarks.join("wat").join("opcodes");
+    // Picks up added and removed files.
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let entries = fs::read_dir(&dir)
+        .unwrap_or_else(|e| fail(&format!("failed to read {}: {}", dir.display(), e)));
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "wat" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+
+    let mut list = String::from("[\n");
+    for name in &names {
+        assemble(benchmarks, &format!("opcodes/{}", name));
+        list.push_str(&format!(
+            "    (\"op_{0}\", wat_module!(\"opcodes/{0}\")),\n",
+            name
+        ));
+    }
+    list.push_str("]\n");
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("opcodes.rs");
+    fs::write(&out, list)
+        .unwrap_or_else(|e| fail(&format!("failed to write {}: {}", out.display(), e)));
+}
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let benchmarks = manifest_dir.join("benchmarks");
+
+    wasm::rerun_if_changed(&benchmarks);
+    emit(&benchmarks, &UNKNOWN);
+    if env::var_os("CARGO_FEATURE_WASI").is_some() {
+        emit(&benchmarks, &WASI);
+    }
+    if env::var_os("CARGO_FEATURE_WEE_ALLOC").is_some() {
+        emit(&benchmarks, &WEE_ALLOC);
+    }
+    if env::var_os("CARGO_FEATURE_SIMD").is_some() {
+        emit(&benchmarks, &SIMD);
+    }
+    for name in WAT_MODULES {
+        assemble(&benchmarks, name);
+    }
+    assemble_opcodes(&benchmarks);
+}
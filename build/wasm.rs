@@ -0,0 +1,117 @@
+//! Builds the `benchmarks` crate to wasm. Shared by the build scripts of this
+//! crate and of `rust-wasm-c-api`, whose tests run the same module.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+/// A wasm build of the `benchmarks` crate.
+pub struct Build {
+    pub target: &'static str,
+    /// Variable giving the path of a prebuilt module instead.
+    pub prebuilt_var: &'static str,
+    /// Variable the crate being built finds the module's path in.
+    pub output_var: &'static str,
+    /// Name of a non-default build, which gets its own target directory,
+    /// `benchmarks/target/<variant>`.
+    pub variant: Option<&'static str>,
+    /// Features of the `benchmarks` crate to enable.
+    pub features: &'static [&'static str],
+    /// Flags passed to rustc.
+    pub rustflags: &'static str,
+}
+
+/// The default build, embedded by `src/lib.rs`.
+pub const UNKNOWN: Build = Build {
+    target: "wasm32-unknown-unknown",
+    prebuilt_var: "WASM_BENCH_WASM",
+    output_var: "WASM_BENCH_BENCHMARKS_WASM",
+    variant: None,
+    features: &[],
+    rustflags: "",
+};
+
+/// Reruns the build script when the sources of the `benchmarks` crate change.
+pub fn rerun_if_changed(benchmarks: &Path) {
+    println!("cargo:rerun-if-changed={}", benchmarks.join("src").display());
+    println!("cargo:rerun-if-changed={}", benchmarks.join("Cargo.toml").display());
+}
+
+pub fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
+
+/// Whether the toolchain building this crate has the standard library for
+/// `target` installed.
+fn target_installed(target: &str) -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = match Command::new(rustc).args(&["--print", "sysroot"]).output() {
+        Ok(output) if output.status.success() => output,
+        // Let cargo report the problem instead.
+        _ => return true,
+    };
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Path::new(&sysroot)
+        .join("lib")
+        .join("rustlib")
+        .join(target)
+        .is_dir()
+}
+
+fn build(benchmarks: &Path, module: &Build) -> PathBuf {
+    if !target_installed(module.target) {
+        fail(&format!(
+            "the {target} target is not installed for this toolchain, which the wasm \
+             benchmarks are built with. Install it with:\n\n    \
+             rustup target add {target} --toolchain nightly\n\n\
+             or point {var} to a prebuilt wasm_bench_benchmarks.wasm.",
+            target = module.target,
+            var = module.prebuilt_var
+        ));
+    }
+
+    let target_dir = match module.variant {
+        Some(variant) => benchmarks.join("target").join(variant),
+        None => benchmarks.join("target"),
+    };
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut command = Command::new(cargo);
+    command
+        .current_dir(benchmarks)
+        .args(&["build", "--release", "--target", module.target])
+        .arg("--target-dir")
+        .arg(&target_dir);
+    if !module.features.is_empty() {
+        command.args(&["--features", &module.features.join(",")]);
+    }
+    let status = command
+        // Flags meant for the host, such as `-C target-cpu=native`, do not
+        // apply to wasm.
+        .env("RUSTFLAGS", module.rustflags)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("CARGO_TARGET_DIR")
+        .status()
+        .unwrap_or_else(|e| fail(&format!("failed to run cargo: {}", e)));
+    if !status.success() {
+        fail(&format!("building the wasm benchmarks in {} failed", benchmarks.display()));
+    }
+
+    target_dir
+        .join(module.target)
+        .join("release")
+        .join("wasm_bench_benchmarks.wasm")
+}
+
+pub fn emit(benchmarks: &Path, module: &Build) {
+    println!("cargo:rerun-if-env-changed={}", module.prebuilt_var);
+    let wasm = match env::var_os(module.prebuilt_var) {
+        Some(path) => PathBuf::from(path),
+        None => build(benchmarks, module),
+    };
+    if !wasm.is_file() {
+        fail(&format!("{} does not exist", wasm.display()));
+    }
+    println!("cargo:rerun-if-changed={}", wasm.display());
+    println!("cargo:rustc-env={}={}", module.output_var, wasm.display());
+}
@@ -3,6 +3,9 @@ extern crate bindgen;
 use std::env;
 use std::path::PathBuf;
 
+#[path = "../build/wasm.rs"]
+mod wasm;
+
 fn main() {
     let path = if let Some(path) = env::var_os("WASM_C_API") {
         path.to_str().unwrap().to_owned()
@@ -36,4 +39,10 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+
+    // The tests run the module the harness embeds.
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let benchmarks = manifest_dir.parent().unwrap().join("benchmarks");
+    wasm::rerun_if_changed(&benchmarks);
+    wasm::emit(&benchmarks, &wasm::UNKNOWN);
 }
@@ -10,9 +10,7 @@ mod tests {
     use std::mem;
     use std::slice;
 
-    static WASM: &'static [u8] = include_bytes!(
-        "../../benchmarks/target/wasm32-unknown-unknown/release/wasm_bench_benchmarks.wasm"
-    );
+    static WASM: &'static [u8] = include_bytes!(env!("WASM_BENCH_BENCHMARKS_WASM"));
 
     #[test]
     fn test_call_wasm() {
@@ -0,0 +1,210 @@
+//! Named baselines and comparisons against them.
+//!
+//! A baseline is a copy of a results file saved under
+//! `target/wasm-bench/baselines/<name>.json`. Comparing a later run against it
+//! classifies every workload/runtime pair as improved, regressed or unchanged,
+//! or as missing when the later run has no result for it.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::results::Results;
+use crate::stats::mann_whitney_u;
+use crate::OUTPUT_DIR;
+
+/// Regression, in percent, above which a comparison fails by default.
+pub const DEFAULT_THRESHOLD: f64 = 5.0;
+/// Default significance level of the Mann-Whitney U test.
+pub const DEFAULT_SIGNIFICANCE: f64 = 0.05;
+/// Changes smaller than this, in percent, are reported as unchanged even
+/// when they are statistically significant.
+const NOISE: f64 = 1.0;
+
+pub fn baselines_dir() -> PathBuf {
+    Path::new(OUTPUT_DIR).join("baselines")
+}
+
+pub fn path(name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.contains(&['/', '\\'][..]) || name.starts_with('.') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid baseline name {:?}", name),
+        ));
+    }
+    Ok(baselines_dir().join(format!("{}.json", name)))
+}
+
+pub fn save(results: &Results, name: &str) -> io::Result<PathBuf> {
+    let path = path(name)?;
+    results.write(&path)?;
+    Ok(path)
+}
+
+pub fn load(name: &str) -> io::Result<Results> {
+    Results::read(&path(name)?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Improved,
+    Regressed,
+    Unchanged,
+    /// In the baseline but not in the current results, e.g. because the
+    /// runtime crashed or stopped running the workload.
+    Missing,
+}
+
+impl Verdict {
+    pub fn name(self) -> &'static str {
+        match self {
+            Verdict::Improved => "improved",
+            Verdict::Regressed => "regressed",
+            Verdict::Unchanged => "unchanged",
+            Verdict::Missing => "missing",
+        }
+    }
+}
+
+pub struct Comparison {
+    pub workload: String,
+    pub runtime: String,
+    pub baseline: f64,
+    /// `None` for missing pairs, as is the change.
+    pub current: Option<f64>,
+    /// Change of the median, in percent; positive is slower.
+    pub change: Option<f64>,
+    /// `None` when there were too few samples to test, as for one-shot
+    /// instruction counts; the change alone decides then.
+    pub p_value: Option<f64>,
+    pub verdict: Verdict,
+}
+
+/// Compares every entry of `current` with the same workload, runtime and unit
+/// in `baseline`, followed by the entries of `baseline` missing from `current`.
+pub fn compare(baseline: &Results, current: &Results, significance: f64) -> Vec<Comparison> {
+    let mut comparisons = Vec::new();
+    for entry in current.results.iter() {
+        let old = match baseline.get(&entry.workload, &entry.runtime) {
+            Some(old) if old.unit == entry.unit => old,
+            _ => continue,
+        };
+        let (before, after) = (old.statistics.median, entry.statistics.median);
+        let change = if before > 0.0 {
+            (after - before) / before * 100.0
+        } else {
+            0.0
+        };
+        let p_value = if old.samples.len() >= 3 && entry.samples.len() >= 3 {
+            Some(mann_whitney_u(&old.samples, &entry.samples))
+        } else {
+            None
+        };
+        let significant = p_value.map_or(true, |p| p < significance);
+        let verdict = if !significant || change.abs() < NOISE {
+            Verdict::Unchanged
+        } else if change > 0.0 {
+            Verdict::Regressed
+        } else {
+            Verdict::Improved
+        };
+        comparisons.push(Comparison {
+            workload: entry.workload.clone(),
+            runtime: entry.runtime.clone(),
+            baseline: before,
+            current: Some(after),
+            change: Some(change),
+            p_value,
+            verdict,
+        });
+    }
+    for old in baseline.results.iter() {
+        match current.get(&old.workload, &old.runtime) {
+            Some(entry) if entry.unit == old.unit => continue,
+            _ => {}
+        }
+        comparisons.push(Comparison {
+            workload: old.workload.clone(),
+            runtime: old.runtime.clone(),
+            baseline: old.statistics.median,
+            current: None,
+            change: None,
+            p_value: None,
+            verdict: Verdict::Missing,
+        });
+    }
+    comparisons
+}
+
+/// Regressions slower than the baseline by more than `threshold` percent, and
+/// missing pairs.
+pub fn failures(comparisons: &[Comparison], threshold: f64) -> Vec<&Comparison> {
+    comparisons
+        .iter()
+        .filter(|c| match c.verdict {
+            Verdict::Regressed => c.change.map_or(false, |change| change > threshold),
+            Verdict::Missing => true,
+            Verdict::Improved | Verdict::Unchanged => false,
+        })
+        .collect()
+}
+
+pub fn write_table<W: Write>(out: &mut W, comparisons: &[Comparison]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<20}{:<16}{:>16}{:>16}{:>10}{:>10}  verdict",
+        "benchmark", "backend", "baseline", "current", "change", "p-value"
+    )?;
+    for c in comparisons {
+        writeln!(
+            out,
+            "{:<20}{:<16}{:>16.1}{:>16}{:>10}{:>10}  {}",
+            c.workload,
+            c.runtime,
+            c.baseline,
+            c.current.map_or("--".to_string(), |current| format!("{:.1}", current)),
+            c.change.map_or("--".to_string(), |change| format!("{:.2}%", change)),
+            c.p_value.map_or("--".to_string(), |p| format!("{:.3}", p)),
+            c.verdict.name(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::{Entry, Unit};
+
+    fn results(workload: &str, samples: Vec<f64>) -> Results {
+        Results::new(vec![
+            Entry::new(workload, "wasmi", Unit::Nanoseconds, samples).unwrap()
+        ])
+    }
+
+    #[test]
+    fn test_compare() {
+        let base: Vec<f64> = (0..20).map(|i| 100.0 + (i % 5) as f64).collect();
+        let slower: Vec<f64> = base.iter().map(|x| x * 1.2).collect();
+        let faster: Vec<f64> = base.iter().map(|x| x * 0.8).collect();
+        let baseline = results("sum", base.clone());
+
+        let same = compare(&baseline, &results("sum", base), DEFAULT_SIGNIFICANCE);
+        assert_eq!(same[0].verdict, Verdict::Unchanged);
+
+        let regressed = compare(&baseline, &results("sum", slower), DEFAULT_SIGNIFICANCE);
+        assert_eq!(regressed[0].verdict, Verdict::Regressed);
+        assert!((regressed[0].change.unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(failures(&regressed, DEFAULT_THRESHOLD).len(), 1);
+        assert!(failures(&regressed, 25.0).is_empty());
+
+        let improved = compare(&baseline, &results("sum", faster), DEFAULT_SIGNIFICANCE);
+        assert_eq!(improved[0].verdict, Verdict::Improved);
+
+        let missing = compare(&baseline, &results("sha1", vec![1.0]), DEFAULT_SIGNIFICANCE);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].workload, "sum");
+        assert_eq!(missing[0].verdict, Verdict::Missing);
+        assert_eq!(failures(&missing, DEFAULT_THRESHOLD).len(), 1);
+        assert!(path("../escape").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Publishing results to a Codespeed instance.
+//!
+//! Results are posted to Codespeed's `result/add/json/` endpoint, as a form
+//! with a single `json` field holding a list of result objects.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::results::{Results, Unit};
+use crate::workload::NATIVE;
+
+pub const DEFAULT_URL: &str = "https://speed.wasmer.io";
+pub const DEFAULT_BRANCH: &str = "master";
+
+/// One result in the format expected by `result/add/json/`. Times are in
+/// seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodespeedResult {
+    pub commitid: String,
+    pub branch: String,
+    pub project: String,
+    pub executable: String,
+    pub benchmark: String,
+    pub environment: String,
+    pub result_value: f64,
+    pub min: f64,
+    pub max: f64,
+    pub std_dev: f64,
+}
+
+/// The Codespeed project a runtime's results are filed under.
+pub fn project(runtime: &str) -> &'static str {
+    if runtime.starts_with("wasmer-") {
+        "wasmer"
+    } else if runtime == "wasmi" {
+        "wasmi"
+    } else if runtime.starts_with("wasmtime") {
+        "wasmtime"
+    } else if runtime.ends_with("-v8") {
+        "v8"
+    } else if runtime == NATIVE {
+        "rust"
+    } else {
+        "other"
+    }
+}
+
+/// Converts the timed entries of `results`.
+///
+/// Each result is filed under `commit` when given, otherwise under the
+/// runtime's commit or version from the results, or the rustc commit for
+/// native code. Entries for which none is known are returned separately as
+/// `workload/runtime` names.
+pub fn convert(
+    results: &Results,
+    environment: &str,
+    branch: &str,
+    commit: Option<&str>,
+) -> (Vec<CodespeedResult>, Vec<String>) {
+    let mut converted = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in results.results.iter().filter(|e| e.unit == Unit::Nanoseconds) {
+        let commitid = commit
+            .or(entry.runtime_commit.as_deref())
+            .or(entry.runtime_version.as_deref())
+            .or(match entry.runtime.as_str() {
+                NATIVE => results.environment.rustc_commit.as_deref(),
+                _ => None,
+            });
+        let commitid = match commitid {
+            Some(commitid) => commitid,
+            None => {
+                skipped.push(format!("{}/{}", entry.workload, entry.runtime));
+                continue;
+            }
+        };
+        let s = &entry.statistics;
+        converted.push(CodespeedResult {
+            commitid: commitid.to_string(),
+            branch: branch.to_string(),
+            project: project(&entry.runtime).to_string(),
+            executable: entry.runtime.clone(),
+            benchmark: entry.workload.clone(),
+            environment: environment.to_string(),
+            result_value: s.mean / 1e9,
+            min: s.min / 1e9,
+            max: s.max / 1e9,
+            std_dev: s.std_dev / 1e9,
+        });
+    }
+    (converted, skipped)
+}
+
+pub struct Client {
+    url: String,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+impl Client {
+    pub fn new(url: &str) -> Client {
+        Client {
+            url: url.trim_end_matches('/').to_string(),
+            retries: 3,
+            retry_delay: Duration::from_secs(2),
+        }
+    }
+
+    /// Number of times a failed upload is retried.
+    pub fn retries(mut self, retries: u32) -> Client {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled after every attempt.
+    pub fn retry_delay(mut self, delay: Duration) -> Client {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Posts `results`. Connection failures and server errors are retried;
+    /// client errors are not, since resending the same data cannot help.
+    pub fn upload(&self, results: &[CodespeedResult]) -> io::Result<()> {
+        let json = serde_json::to_string(results)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let url = format!("{}/result/add/json/", self.url);
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let response = ureq::post(&url)
+                .timeout_connect(10_000)
+                .timeout_read(60_000)
+                .send_form(&[("json", &json)]);
+            let status = response.status();
+            let error = if let Some(e) = response.synthetic_error() {
+                e.to_string()
+            } else if response.ok() {
+                return Ok(());
+            } else {
+                let body = response.into_string().unwrap_or_default();
+                let error = format!("{} returned {}: {}", url, status, body.trim());
+                if status < 500 {
+                    return Err(io::Error::new(io::ErrorKind::Other, error));
+                }
+                error
+            };
+            if attempt >= self.retries {
+                return Err(io::Error::new(io::ErrorKind::Other, error));
+            }
+            attempt += 1;
+            eprintln!("upload failed ({}), retrying in {:?}", error, delay);
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! The machine and toolchain a run was measured with, recorded in every
+//! results file so runs from different hosts or compilers are not mistaken for
+//! each other.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::lockfile;
+#[cfg(feature = "simd")]
+use crate::SIMD_WASM;
+#[cfg(feature = "threads")]
+use crate::THREADS_WASM;
+#[cfg(feature = "wasi")]
+use crate::WASI_WASM;
+#[cfg(feature = "wee-alloc")]
+use crate::WEE_ALLOC_WASM;
+#[cfg(feature = "proposals")]
+use crate::{BULK_MEMORY_WASM, MULTI_VALUE_WASM, MVP_WASM, REFERENCE_TYPES_WASM};
+use crate::{LARGE_WASM, OPCODE_WASMS, SMALL_WASM, WASM};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Environment {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub cpu_model: Option<String>,
+    /// Logical CPUs online.
+    pub cores: Option<u32>,
+    pub governor: Option<String>,
+    pub kernel: Option<String>,
+    /// `rustc --version` output, e.g. `rustc 1.40.0-nightly (4a8c5b20c 2019-10-23)`.
+    pub rustc: Option<String>,
+    pub rustc_commit: Option<String>,
+    pub wasmer_commit: Option<String>,
+    pub wasmi_version: Option<String>,
+    /// SHA-256 of the benchmarked `.wasm` modules, by file name.
+    pub wasm_sha256: BTreeMap<String, String>,
+}
+
+impl Environment {
+    pub fn current() -> Environment {
+        let (rustc, rustc_commit) = rustc();
+        Environment {
+            hostname: hostname(),
+            os: env::consts::OS.to_string(),
+            arch: env::consts::ARCH.to_string(),
+            cpu_model: cpu_model(),
+            cores: cores(),
+            governor: read_trimmed("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor"),
+            kernel: read_trimmed("/proc/sys/kernel/osrelease"),
+            rustc,
+            rustc_commit,
+            wasmer_commit: lockfile::package("wasmer-runtime-core")
+                .and_then(|p| p.commit().map(String::from)),
+            wasmi_version: lockfile::package("wasmi").map(|p| p.version),
+            wasm_sha256: modules()
+                .iter()
+                .map(|&(name, module)| (name.to_string(), sha256(module)))
+                // The opcode modules are named after their workloads.
+                .chain(
+                    OPCODE_WASMS
+                        .iter()
+                        .map(|&(name, module)| (format!("{}.wasm", name), sha256(module))),
+                )
+                .collect(),
+        }
+    }
+}
+
+/// The benchmarked modules, by file name.
+fn modules() -> Vec<(&'static str, &'static [u8])> {
+    #[allow(unused_mut)]
+    let mut modules = vec![
+        ("wasm_bench_benchmarks.wasm", WASM),
+        ("printf.wasm", SMALL_WASM),
+        ("lua.wasm", LARGE_WASM),
+    ];
+    #[cfg(feature = "wasi")]
+    modules.push(("wasm_bench_benchmarks.wasi.wasm", WASI_WASM));
+    #[cfg(feature = "simd")]
+    modules.push(("wasm_bench_benchmarks.simd.wasm", SIMD_WASM));
+    #[cfg(feature = "threads")]
+    modules.push(("threads.wasm", THREADS_WASM));
+    #[cfg(feature = "proposals")]
+    modules.extend(vec![
+        ("bulk_memory.wasm", BULK_MEMORY_WASM),
+        ("multi_value.wasm", MULTI_VALUE_WASM),
+        ("reference_types.wasm", REFERENCE_TYPES_WASM),
+        ("mvp.wasm", MVP_WASM),
+    ]);
+    #[cfg(feature = "wee-alloc")]
+    modules.push(("wasm_bench_benchmarks.wee_alloc.wasm", WEE_ALLOC_WASM));
+    modules
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+        .filter(|contents| !contents.is_empty())
+}
+
+fn hostname() -> String {
+    read_trimmed("/proc/sys/kernel/hostname")
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.splitn(2, ':').nth(1))
+        .map(|model| model.trim().to_string())
+}
+
+#[cfg(unix)]
+fn cores() -> Option<u32> {
+    let cores = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if cores > 0 {
+        Some(cores as u32)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn cores() -> Option<u32> {
+    env::var("NUMBER_OF_PROCESSORS").ok()?.parse().ok()
+}
+
+/// Version and commit of the `rustc` cargo runs. Under `cargo +nightly`,
+/// rustup passes the toolchain on to child processes through
+/// `RUSTUP_TOOLCHAIN`, so this is the compiler the harness was built with.
+fn rustc() -> (Option<String>, Option<String>) {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = match Command::new(rustc).arg("-vV").output() {
+        Ok(ref output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        _ => return (None, None),
+    };
+    let version = output.lines().next().map(String::from);
+    let commit = output
+        .lines()
+        .find(|line| line.starts_with("commit-hash:"))
+        .map(|line| line["commit-hash:".len()..].trim().to_string())
+        .filter(|commit| commit != "unknown");
+    (version, commit)
+}
+
+fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
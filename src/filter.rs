@@ -0,0 +1,137 @@
+//! Selecting which workloads and runtimes the harnesses run.
+//!
+//! Filters are comma-separated lists of name fragments: `--runtime llvm,wasmi`
+//! keeps `wasmer-llvm` and `wasmi`, `--workload fib,nbody` keeps `fibonacci`
+//! and `nbody`. Native code is matched by `native`. The same lists can be given
+//! through `WASM_BENCH_RUNTIMES` and `WASM_BENCH_WORKLOADS`; command-line flags
+//! take precedence.
+
+use std::env;
+
+pub const RUNTIMES_VAR: &str = "WASM_BENCH_RUNTIMES";
+pub const WORKLOADS_VAR: &str = "WASM_BENCH_WORKLOADS";
+
+const RUNTIME_FLAG: &str = "--runtime";
+const WORKLOAD_FLAG: &str = "--workload";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    runtimes: Option<Vec<String>>,
+    workloads: Option<Vec<String>>,
+}
+
+fn parse_list(list: &str) -> Option<Vec<String>> {
+    let patterns: Vec<String> = list
+        .split(',')
+        .map(|pattern| pattern.trim().to_lowercase())
+        .filter(|pattern| !pattern.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        None
+    } else {
+        Some(patterns)
+    }
+}
+
+fn matches(patterns: &Option<Vec<String>>, name: &str) -> bool {
+    match patterns {
+        Some(patterns) => {
+            let name = name.to_lowercase();
+            patterns.iter().any(|pattern| name.contains(pattern.as_str()))
+        }
+        None => true,
+    }
+}
+
+impl Filter {
+    pub fn new(runtimes: Option<&str>, workloads: Option<&str>) -> Filter {
+        Filter {
+            runtimes: runtimes.and_then(parse_list),
+            workloads: workloads.and_then(parse_list),
+        }
+    }
+
+    pub fn from_env() -> Filter {
+        Filter::new(
+            env::var(RUNTIMES_VAR).ok().as_deref(),
+            env::var(WORKLOADS_VAR).ok().as_deref(),
+        )
+    }
+
+    /// Removes `--runtime` and `--workload` flags from `args`, returning the
+    /// filter they describe (falling back to the environment for missing
+    /// flags) and the remaining arguments.
+    pub fn from_args(args: &[String]) -> Result<(Filter, Vec<String>), String> {
+        let mut filter = Filter::from_env();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut flag_value = |flag: &str| -> Result<Option<String>, String> {
+                if arg == flag {
+                    args.next()
+                        .cloned()
+                        .map(Some)
+                        .ok_or_else(|| format!("{} needs a value", flag))
+                } else if arg.starts_with(flag) && arg[flag.len()..].starts_with('=') {
+                    Ok(Some(arg[flag.len() + 1..].to_string()))
+                } else {
+                    Ok(None)
+                }
+            };
+            if let Some(value) = flag_value(RUNTIME_FLAG)? {
+                filter.runtimes = parse_list(&value);
+            } else if let Some(value) = flag_value(WORKLOAD_FLAG)? {
+                filter.workloads = parse_list(&value);
+            } else {
+                rest.push(arg.clone());
+            }
+        }
+        Ok((filter, rest))
+    }
+
+    pub fn runtime(&self, name: &str) -> bool {
+        matches(&self.runtimes, name)
+    }
+
+    pub fn workload(&self, name: &str) -> bool {
+        matches(&self.workloads, name)
+    }
+
+    /// The environment variables describing this filter, for handing it to a
+    /// child process.
+    pub fn to_env(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if let Some(ref runtimes) = self.runtimes {
+            vars.push((RUNTIMES_VAR, runtimes.join(",")));
+        }
+        if let Some(ref workloads) = self.workloads {
+            vars.push((WORKLOADS_VAR, workloads.join(",")));
+        }
+        vars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_args() {
+        let args: Vec<String> = ["--bench", "--runtime", "llvm,WASMI", "--workload=fib,", "sum"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let (filter, rest) = Filter::from_args(&args).unwrap();
+        assert_eq!(rest, vec!["--bench", "sum"]);
+        assert!(filter.runtime("wasmer-llvm"));
+        assert!(filter.runtime("wasmi"));
+        assert!(!filter.runtime("wasmer-clif"));
+        assert!(!filter.runtime("rust-native"));
+        assert!(filter.workload("fibonacci"));
+        assert!(!filter.workload("nbody"));
+
+        assert!(Filter::from_args(&["--runtime".to_string()]).is_err());
+        // A list of blank names selects everything.
+        assert!(Filter::new(None, Some(" , ")).workload("nbody"));
+    }
+}
@@ -0,0 +1,321 @@
+//! One-shot instruction counts, in the spirit of `iai`.
+//!
+//! Every workload is run exactly once per runtime and the number of
+//! instructions it retires is reported. Unlike wall-clock samples these numbers
+//! barely move between runs, even on shared CI machines, so they can be
+//! compared against the previous run with tight thresholds.
+//!
+//! Instructions are counted with `perf_event_open` when the host allows it.
+//! Otherwise, if `valgrind` is installed, the harness re-executes itself under
+//! cachegrind twice (with and without the measured operation) and reports the
+//! difference.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::filter::Filter;
+use crate::perf::{Counters, Event};
+use crate::runtime::{runtimes, Error, Runtime};
+use crate::workload::{check, workload, workloads, Kind, Workload, NATIVE};
+use crate::OUTPUT_DIR;
+
+/// Argument marking a child process started under cachegrind.
+pub const CHILD_FLAG: &str = "--icount-child";
+
+/// Relative change above which a count is flagged, overridable through this
+/// variable (in percent).
+pub const THRESHOLD_VAR: &str = "WASM_BENCH_ICOUNT_THRESHOLD";
+const DEFAULT_THRESHOLD: f64 = 1.0;
+
+/// Exit status of the harness when a count changed by more than the
+/// threshold.
+pub const CHANGED_EXIT_CODE: i32 = 2;
+
+pub enum Counter {
+    Perf,
+    Cachegrind(PathBuf),
+}
+
+impl Counter {
+    /// Prefers in-process perf counters, then cachegrind.
+    pub fn detect() -> Option<Counter> {
+        if Counters::open(&[Event::Instructions]).is_ok() {
+            return Some(Counter::Perf);
+        }
+        find_in_path("valgrind").map(Counter::Cachegrind)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Counter::Perf => "perf",
+            Counter::Cachegrind(_) => "cachegrind",
+        }
+    }
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|path| path.is_file())
+    })
+}
+
+fn other<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// Sets `workload` up on `runtime` (natively when `None`), checks one run of
+/// the measured operation and passes it to `measure`.
+fn with_operation<T>(
+    workload: &Workload,
+    runtime: Option<&dyn Runtime>,
+    measure: impl FnOnce(&mut dyn FnMut() -> Result<(), Error>) -> T,
+) -> Result<T, Error> {
+    match (&workload.kind, runtime) {
+        (Kind::Compile, Some(runtime)) => Ok(measure(&mut || runtime.compile(workload.module))),
+        (Kind::Instantiate, Some(runtime)) => {
+            let mut instantiate = runtime.prepare_instantiate(workload.module)?;
+            check(&mut instantiate, None)?;
+            Ok(measure(&mut || instantiate().map(|_| ())))
+        }
+        (Kind::Call { call, .. }, Some(runtime)) => {
+            let instance = workload.instantiate(runtime)?;
+            let mut func = instance.prepare(call)?;
+            check(&mut func, workload.expected())?;
+            Ok(measure(&mut || func().map(|_| ())))
+        }
+        (Kind::Threads { .. }, Some(runtime)) => {
+            let mut run = workload.prepare_threads(runtime)?;
+            check(&mut run, workload.expected())?;
+            Ok(measure(&mut || run().map(|_| ())))
+        }
+        (Kind::Program { .. }, Some(runtime)) => {
+            let mut setup = workload.prepare_program(runtime)?;
+            check(&mut || setup()?(), None)?;
+            let mut run = setup()?;
+            Ok(measure(&mut || run().map(|_| ())))
+        }
+        (_, None) => match workload.native() {
+            Some(native) => Ok(measure(&mut || {
+                native();
+                Ok(())
+            })),
+            None => Err(Error::Unsupported(format!(
+                "{} has no native version",
+                workload.name
+            ))),
+        },
+    }
+}
+
+fn count_with_perf(workload: &Workload, runtime: Option<&dyn Runtime>) -> io::Result<u64> {
+    let counters = Counters::open(&[Event::Instructions])?;
+    let result = with_operation(workload, runtime, |operation| {
+        counters.enable();
+        let result = operation();
+        counters.disable();
+        result
+    });
+    result.and_then(|r| r).map_err(other)?;
+    counters
+        .read()?
+        .instructions
+        .ok_or_else(|| other("instructions were not counted"))
+}
+
+/// Extracts the instruction total from a cachegrind output file.
+pub fn parse_cachegrind_summary(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find(|line| line.starts_with("summary:"))
+        .and_then(|line| line["summary:".len()..].split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+}
+
+fn run_under_cachegrind(
+    valgrind: &Path,
+    workload: &str,
+    runtime: &str,
+    times: u32,
+) -> io::Result<u64> {
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let out_file = Path::new(OUTPUT_DIR).join("cachegrind.out");
+    let output = Command::new(valgrind)
+        .arg("--tool=cachegrind")
+        .arg("--cache-sim=no")
+        .arg(format!("--cachegrind-out-file={}", out_file.display()))
+        .arg(env::current_exe()?)
+        .arg(CHILD_FLAG)
+        .arg(workload)
+        .arg(runtime)
+        .arg(times.to_string())
+        .output()?;
+    if !output.status.success() {
+        return Err(other(format!(
+            "cachegrind run failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    parse_cachegrind_summary(&fs::read_to_string(&out_file)?)
+        .ok_or_else(|| other("no summary line in cachegrind output"))
+}
+
+fn count_with_cachegrind(valgrind: &Path, workload: &str, runtime: &str) -> io::Result<u64> {
+    let with_operation = run_under_cachegrind(valgrind, workload, runtime, 1)?;
+    let setup_only = run_under_cachegrind(valgrind, workload, runtime, 0)?;
+    Ok(with_operation.saturating_sub(setup_only))
+}
+
+/// Entry point of a child started by [`count_with_cachegrind`]: sets up the
+/// workload and runs its operation `times` times.
+pub fn run_child(args: &[String]) -> Result<(), Error> {
+    let (workload_name, runtime_name, times) = match args {
+        [workload, runtime, times] => (workload, runtime, times.parse().unwrap_or(1)),
+        _ => return Err(Error::Unsupported(format!("bad child arguments {:?}", args))),
+    };
+    let workload = workload(workload_name)
+        .ok_or_else(|| Error::Unsupported(format!("unknown workload {}", workload_name)))?;
+    let runtimes = runtimes();
+    let runtime = if runtime_name == NATIVE {
+        None
+    } else {
+        Some(
+            runtimes
+                .iter()
+                .find(|r| r.name() == runtime_name)
+                .ok_or_else(|| Error::Unsupported(format!("unknown runtime {}", runtime_name)))?
+                .as_ref(),
+        )
+    };
+    with_operation(&workload, runtime, |operation| {
+        for _ in 0..times {
+            operation()?;
+        }
+        Ok(())
+    })?
+}
+
+fn counts_path() -> PathBuf {
+    Path::new(OUTPUT_DIR).join("icount.csv")
+}
+
+fn read_counts(path: &Path) -> BTreeMap<(String, String), u64> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let workload = fields.next()?.to_string();
+            let runtime = fields.next()?.to_string();
+            let count = fields.next()?.parse().ok()?;
+            Some(((workload, runtime), count))
+        })
+        .collect()
+}
+
+fn write_counts(path: &Path, counts: &BTreeMap<(String, String), u64>) -> io::Result<()> {
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "benchmark,backend,instructions")?;
+    for ((workload, runtime), count) in counts {
+        writeln!(file, "{},{},{}", workload, runtime, count)?;
+    }
+    Ok(())
+}
+
+fn threshold() -> f64 {
+    env::var(THRESHOLD_VAR)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_THRESHOLD)
+}
+
+/// Counts every workload on every runtime selected by `filter`, prints the
+/// counts with the change since the previous run and stores them in
+/// `target/wasm-bench/icount.csv`. Returns the number of pairs whose count
+/// changed by more than the threshold.
+pub fn run(filter: &Filter) -> io::Result<usize> {
+    let counter = Counter::detect().ok_or_else(|| {
+        other("instruction counting needs perf_event_open access or valgrind in PATH")
+    })?;
+    println!("counting instructions with {}", counter.name());
+
+    let path = counts_path();
+    let previous = read_counts(&path);
+    let threshold = threshold();
+    let runtimes = runtimes();
+    // Pairs that are filtered out keep their previous counts.
+    let mut counts = previous.clone();
+    let mut changed = 0;
+
+    for workload in workloads().into_iter().filter(|w| filter.workload(w.name)) {
+        let mut targets: Vec<Option<&dyn Runtime>> = Vec::new();
+        if workload.native().is_some() && filter.runtime(NATIVE) {
+            targets.push(None);
+        }
+        for runtime in runtimes.iter().filter(|r| filter.runtime(r.name())) {
+            if workload.runs_on(runtime.as_ref()) {
+                targets.push(Some(runtime.as_ref()));
+            } else if workload.reports_unsupported() {
+                println!("{}/{}", workload.name, runtime.name());
+                println!("  unsupported");
+            }
+        }
+
+        for runtime in targets {
+            let runtime_name = runtime.map_or(NATIVE, |r| r.name());
+            let count = match counter {
+                Counter::Perf => count_with_perf(&workload, runtime),
+                Counter::Cachegrind(ref valgrind) => {
+                    count_with_cachegrind(valgrind, workload.name, runtime_name)
+                }
+            };
+            println!("{}/{}", workload.name, runtime_name);
+            let key = (workload.name.to_string(), runtime_name.to_string());
+            let count = match count {
+                Ok(count) => count,
+                Err(e) => {
+                    println!("  failed: {}", e);
+                    // A stale count would look current.
+                    counts.remove(&key);
+                    continue;
+                }
+            };
+            match previous.get(&key) {
+                Some(&old) if old > 0 => {
+                    let change = (count as f64 - old as f64) / old as f64 * 100.0;
+                    let flag = if change.abs() > threshold {
+                        changed += 1;
+                        " (changed)"
+                    } else {
+                        ""
+                    };
+                    println!("  Instructions: {:>15} ({:+.3}%){}", count, change, flag);
+                }
+                _ => println!("  Instructions: {:>15}", count),
+            }
+            counts.insert(key, count);
+        }
+    }
+
+    write_counts(&path, &counts)?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cachegrind_summary() {
+        let output = "desc: I1 cache:\ncmd: bench\nevents: Ir\nfl=foo.rs\n1 10\nsummary: 123456\n";
+        assert_eq!(parse_cachegrind_summary(output), Some(123456));
+        assert_eq!(parse_cachegrind_summary("events: Ir\n"), None);
+    }
+}
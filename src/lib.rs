@@ -1 +1,64 @@
+#[macro_use]
+extern crate lazy_static;
 
+pub mod baseline;
+pub mod codespeed;
+pub mod environment;
+pub mod filter;
+pub mod icount;
+pub mod lockfile;
+pub mod perf;
+pub mod preflight;
+pub mod report;
+pub mod results;
+pub mod runtime;
+pub mod stats;
+pub mod synthetic;
+pub mod workload;
+
+/// Directory the harness writes its own reports to.
+pub const OUTPUT_DIR: &str = "target/wasm-bench";
+
+/// The `benchmarks` crate compiled to wasm by `build.rs`.
+pub static WASM: &'static [u8] = include_bytes!(env!("WASM_BENCH_BENCHMARKS_WASM"));
+
+pub static SMALL_WASM: &'static [u8] = include_bytes!("../benchmarks/src/printf.wasm");
+
+pub static LARGE_WASM: &'static [u8] = include_bytes!("../benchmarks/src/lua.wasm");
+
+/// The `benchmarks` crate compiled to `wasm32-wasip1` by `build.rs`.
+#[cfg(feature = "wasi")]
+pub static WASI_WASM: &'static [u8] = include_bytes!(env!("WASM_BENCH_BENCHMARKS_WASI_WASM"));
+
+/// The `benchmarks` crate compiled to wasm with `wee_alloc` as its allocator.
+#[cfg(feature = "wee-alloc")]
+pub static WEE_ALLOC_WASM: &'static [u8] =
+    include_bytes!(env!("WASM_BENCH_BENCHMARKS_WEE_ALLOC_WASM"));
+
+/// A module of `benchmarks/wat`, assembled by `build.rs`.
+macro_rules! wat_module {
+    ($name:expr) => {
+        include_bytes!(concat!(env!("OUT_DIR"), "/", $name, ".wasm"))
+    };
+}
+
+#[cfg(feature = "threads")]
+pub static THREADS_WASM: &'static [u8] = wat_module!("threads");
+
+/// The opcode microbenchmarks of `benchmarks/wat/opcodes`, by workload name.
+pub static OPCODE_WASMS: &'static [(&'static str, &'static [u8])] =
+    &include!(concat!(env!("OUT_DIR"), "/opcodes.rs"));
+
+/// Post-MVP proposals and their MVP baseline.
+#[cfg(feature = "proposals")]
+pub static BULK_MEMORY_WASM: &'static [u8] = wat_module!("bulk_memory");
+#[cfg(feature = "proposals")]
+pub static MULTI_VALUE_WASM: &'static [u8] = wat_module!("multi_value");
+#[cfg(feature = "proposals")]
+pub static REFERENCE_TYPES_WASM: &'static [u8] = wat_module!("reference_types");
+#[cfg(feature = "proposals")]
+pub static MVP_WASM: &'static [u8] = wat_module!("mvp");
+
+/// The `benchmarks` crate compiled to wasm with SIMD instructions.
+#[cfg(feature = "simd")]
+pub static SIMD_WASM: &'static [u8] = include_bytes!(env!("WASM_BENCH_BENCHMARKS_SIMD_WASM"));
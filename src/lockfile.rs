@@ -0,0 +1,91 @@
+//! Versions of the runtimes this harness was built against, read from the
+//! `Cargo.lock` embedded at compile time.
+
+use serde::Deserialize;
+
+static CARGO_LOCK: &str = include_str!("../Cargo.lock");
+
+lazy_static! {
+    /// The packages of `CARGO_LOCK`, parsed on first use.
+    static ref PACKAGES: Vec<Package> = parse(CARGO_LOCK).unwrap_or_default();
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+}
+
+impl Package {
+    /// Commit of a git dependency, e.g. the `8653216b...` of
+    /// `git+https://github.com/wasmerio/wasmer.git#8653216b...`.
+    pub fn commit(&self) -> Option<&str> {
+        let source = self.source.as_ref()?;
+        if !source.starts_with("git+") {
+            return None;
+        }
+        source.rsplit('#').next()
+    }
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<Package>,
+}
+
+pub fn parse(contents: &str) -> Result<Vec<Package>, toml::de::Error> {
+    toml::from_str::<Lockfile>(contents).map(|lockfile| lockfile.package)
+}
+
+/// Looks `name` up in the lock file the harness was built with.
+pub fn package(name: &str) -> Option<Package> {
+    PACKAGES
+        .iter()
+        .find(|package| package.name == name)
+        .cloned()
+}
+
+/// The crate implementing `runtime`, if it is a Cargo dependency.
+pub fn runtime_package(runtime: &str) -> Option<Package> {
+    if runtime.starts_with("wasmer-") {
+        package("wasmer-runtime-core")
+    } else if runtime == "wasmi" {
+        package("wasmi")
+    } else if runtime.starts_with("wasmtime") {
+        package("wasmtime")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let packages = parse(
+            r#"
+[[package]]
+name = "wasmer-runtime-core"
+version = "0.9.0"
+source = "git+https://github.com/wasmerio/wasmer.git#8653216b1a3ab54d49b5e7e8621f93823e62de40"
+
+[[package]]
+name = "wasmi"
+version = "0.4.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+        )
+        .unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            packages[0].commit(),
+            Some("8653216b1a3ab54d49b5e7e8621f93823e62de40")
+        );
+        assert_eq!(packages[1].version, "0.4.5");
+        assert_eq!(packages[1].commit(), None);
+    }
+}
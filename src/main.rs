@@ -0,0 +1,222 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use lazy_static::lazy_static;
+use structopt::StructOpt;
+use wasm_bench::baseline;
+use wasm_bench::codespeed::{self, Client};
+use wasm_bench::preflight;
+use wasm_bench::report;
+use wasm_bench::results::{self, Results};
+use wasm_bench::workload::NATIVE;
+
+lazy_static! {
+    /// The defaults of `compare`, as its options take them.
+    static ref DEFAULT_THRESHOLD: String = baseline::DEFAULT_THRESHOLD.to_string();
+    static ref DEFAULT_SIGNIFICANCE: String = baseline::DEFAULT_SIGNIFICANCE.to_string();
+}
+
+#[derive(StructOpt)]
+#[structopt(name = "wasm-bench", about = "Compare the performance of WebAssembly runtimes")]
+enum Command {
+    /// Print statistics and ratios of the last `cargo bench` run
+    #[structopt(name = "report")]
+    Report {
+        /// Results file written by the harness [default: target/wasm-bench/results.json]
+        #[structopt(long = "results", parse(from_os_str))]
+        results: Option<PathBuf>,
+        /// Rebuild the results from this criterion output directory instead
+        #[structopt(long = "criterion-dir", parse(from_os_str), conflicts_with = "results")]
+        criterion_dir: Option<PathBuf>,
+        /// Runtime the other runtimes' times are compared with
+        #[structopt(long = "against", default_value = NATIVE)]
+        against: String,
+    },
+    /// Upload results to a Codespeed instance
+    #[structopt(name = "upload")]
+    Upload {
+        /// Results file written by the harness [default: target/wasm-bench/results.json]
+        #[structopt(long = "results", parse(from_os_str))]
+        results: Option<PathBuf>,
+        /// Codespeed instance
+        #[structopt(long = "url", default_value = codespeed::DEFAULT_URL)]
+        url: String,
+        /// Environment name registered in Codespeed [default: the host the results come from]
+        #[structopt(long = "environment")]
+        environment: Option<String>,
+        #[structopt(long = "branch", default_value = codespeed::DEFAULT_BRANCH)]
+        branch: String,
+        /// Commit to file every result under, instead of each runtime's own
+        #[structopt(long = "commit")]
+        commit: Option<String>,
+        /// Times a failed upload is retried
+        #[structopt(long = "retries", default_value = "3")]
+        retries: u32,
+        /// Print the results instead of uploading them
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
+    /// Save results as a named baseline
+    #[structopt(name = "save-baseline")]
+    SaveBaseline {
+        name: String,
+        /// Results file written by the harness [default: target/wasm-bench/results.json]
+        #[structopt(long = "results", parse(from_os_str))]
+        results: Option<PathBuf>,
+    },
+    /// Compare results with a named baseline, exiting with status 2 on regressions
+    #[structopt(name = "compare")]
+    Compare {
+        baseline: String,
+        /// Results file written by the harness [default: target/wasm-bench/results.json]
+        #[structopt(long = "results", parse(from_os_str))]
+        results: Option<PathBuf>,
+        /// Slowdown, in percent, above which a regression fails the comparison
+        #[structopt(long = "threshold", default_value = &DEFAULT_THRESHOLD)]
+        threshold: f64,
+        /// Significance level of the Mann-Whitney U test
+        #[structopt(long = "significance", default_value = &DEFAULT_SIGNIFICANCE)]
+        significance: f64,
+    },
+    /// Check the machine for conditions that make timings unreliable
+    #[structopt(name = "preflight")]
+    Preflight {
+        /// Fail if any check does not pass
+        #[structopt(long = "strict")]
+        strict: bool,
+        /// CPU the benchmarks will be pinned to, checked for isolation
+        #[structopt(long = "pin-cpu")]
+        pin_cpu: Option<usize>,
+    },
+}
+
+/// Exit status of `compare` when a regression exceeds the threshold.
+const REGRESSION_EXIT_CODE: i32 = 2;
+
+struct Upload {
+    url: String,
+    environment: Option<String>,
+    branch: String,
+    commit: Option<String>,
+    retries: u32,
+    dry_run: bool,
+}
+
+fn upload(results: &Results, options: Upload) -> io::Result<()> {
+    let environment = options
+        .environment
+        .unwrap_or_else(|| results.environment.hostname.clone());
+    let (converted, skipped) = codespeed::convert(
+        results,
+        &environment,
+        &options.branch,
+        options.commit.as_deref(),
+    );
+    for name in skipped {
+        eprintln!("skipping {}: no runtime commit or version, pass --commit", name);
+    }
+    if options.dry_run {
+        let json = serde_json::to_string_pretty(&converted)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        println!("{}", json);
+        return Ok(());
+    }
+    println!("uploading {} results to {}", converted.len(), options.url);
+    Client::new(&options.url)
+        .retries(options.retries)
+        .upload(&converted)
+}
+
+fn compare(name: &str, results: &Path, threshold: f64, significance: f64) -> io::Result<()> {
+    let baseline = baseline::load(name)?;
+    let results = Results::read(results)?;
+    let comparisons = baseline::compare(&baseline, &results, significance);
+    baseline::write_table(&mut io::stdout(), &comparisons)?;
+
+    let failures = baseline::failures(&comparisons, threshold);
+    if !failures.is_empty() {
+        println!();
+        for c in failures.iter() {
+            match c.change {
+                Some(change) => println!(
+                    "{}/{} regressed by {:.2}% (threshold {}%)",
+                    c.workload, c.runtime, change, threshold
+                ),
+                None => println!("{}/{} is missing from the results", c.workload, c.runtime),
+            }
+        }
+        process::exit(REGRESSION_EXIT_CODE);
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = match Command::from_args() {
+        Command::Report {
+            results,
+            criterion_dir,
+            against,
+        } => match criterion_dir {
+            Some(dir) => Results::from_criterion(&dir),
+            None => Results::read(&results.unwrap_or_else(results::default_path)),
+        }
+        .and_then(|results| {
+            report::print_table(&report::collect(&results, &against), &against)?;
+            report::print_metering_overhead(&results)
+        }),
+        Command::Upload {
+            results,
+            url,
+            environment,
+            branch,
+            commit,
+            retries,
+            dry_run,
+        } => Results::read(&results.unwrap_or_else(results::default_path)).and_then(|results| {
+            upload(
+                &results,
+                Upload {
+                    url,
+                    environment,
+                    branch,
+                    commit,
+                    retries,
+                    dry_run,
+                },
+            )
+        }),
+        Command::SaveBaseline { name, results } => {
+            Results::read(&results.unwrap_or_else(results::default_path))
+                .and_then(|results| baseline::save(&results, &name))
+                .map(|path| println!("saved baseline {} to {}", name, path.display()))
+        }
+        Command::Compare {
+            baseline,
+            results,
+            threshold,
+            significance,
+        } => compare(
+            &baseline,
+            &results.unwrap_or_else(results::default_path),
+            threshold,
+            significance,
+        ),
+        Command::Preflight { strict, pin_cpu } => {
+            let issues = preflight::check(pin_cpu);
+            for issue in issues.iter() {
+                println!("{}: {}", issue.check, issue.message);
+            }
+            if issues.is_empty() {
+                println!("all pre-flight checks passed");
+            } else if strict {
+                process::exit(1);
+            }
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,397 @@
+//! Hardware performance counters, collected in a batch of iterations of every
+//! benchmark run separately from criterion's timing.
+//!
+//! Counting is opt-in: set `WASM_BENCH_COUNTERS=1` when running `cargo bench`.
+//! Counters are opened with Linux `perf_event_open`. When that is not possible
+//! (another OS, a container without `CAP_PERFMON`, a strict
+//! `perf_event_paranoid`) a warning is printed once and the benchmarks fall back
+//! to criterion's wall-clock timing only.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::OUTPUT_DIR;
+
+/// Environment variable enabling hardware counters.
+pub const ENV_VAR: &str = "WASM_BENCH_COUNTERS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Instructions,
+    Cycles,
+    BranchMisses,
+    CacheMisses,
+}
+
+impl Event {
+    pub const ALL: [Event; 4] = [
+        Event::Instructions,
+        Event::Cycles,
+        Event::BranchMisses,
+        Event::CacheMisses,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::Instructions => "instructions",
+            Event::Cycles => "cycles",
+            Event::BranchMisses => "branch-misses",
+            Event::CacheMisses => "cache-misses",
+        }
+    }
+}
+
+/// Counter values. Events the host could not count are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Counts {
+    pub instructions: Option<u64>,
+    pub cycles: Option<u64>,
+    pub branch_misses: Option<u64>,
+    pub cache_misses: Option<u64>,
+}
+
+impl Counts {
+    pub fn get(&self, event: Event) -> Option<u64> {
+        match event {
+            Event::Instructions => self.instructions,
+            Event::Cycles => self.cycles,
+            Event::BranchMisses => self.branch_misses,
+            Event::CacheMisses => self.cache_misses,
+        }
+    }
+
+    fn slot(&mut self, event: Event) -> &mut Option<u64> {
+        match event {
+            Event::Instructions => &mut self.instructions,
+            Event::Cycles => &mut self.cycles,
+            Event::BranchMisses => &mut self.branch_misses,
+            Event::CacheMisses => &mut self.cache_misses,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::{Counts, Event};
+    use perf_event_open_sys as perf;
+    use perf_event_open_sys::bindings::{
+        perf_event_attr, perf_event_ioc_flags_PERF_IOC_FLAG_GROUP as FLAG_GROUP,
+        perf_event_read_format_PERF_FORMAT_GROUP as FORMAT_GROUP,
+        perf_event_read_format_PERF_FORMAT_TOTAL_TIME_ENABLED as FORMAT_TIME_ENABLED,
+        perf_event_read_format_PERF_FORMAT_TOTAL_TIME_RUNNING as FORMAT_TIME_RUNNING,
+        perf_hw_id_PERF_COUNT_HW_BRANCH_MISSES, perf_hw_id_PERF_COUNT_HW_CACHE_MISSES,
+        perf_hw_id_PERF_COUNT_HW_CPU_CYCLES, perf_hw_id_PERF_COUNT_HW_INSTRUCTIONS,
+        perf_type_id_PERF_TYPE_HARDWARE,
+    };
+    use std::io;
+    use std::mem;
+    use std::os::raw::c_int;
+
+    fn config(event: Event) -> u64 {
+        (match event {
+            Event::Instructions => perf_hw_id_PERF_COUNT_HW_INSTRUCTIONS,
+            Event::Cycles => perf_hw_id_PERF_COUNT_HW_CPU_CYCLES,
+            Event::BranchMisses => perf_hw_id_PERF_COUNT_HW_BRANCH_MISSES,
+            Event::CacheMisses => perf_hw_id_PERF_COUNT_HW_CACHE_MISSES,
+        }) as u64
+    }
+
+    /// A group of counters for the calling thread, user space only.
+    pub struct Counters {
+        // The first entry is the group leader.
+        fds: Vec<(Event, c_int)>,
+    }
+
+    impl Counters {
+        /// Opens as many of `events` as the host supports. Fails only if none
+        /// of them could be opened.
+        pub fn open(events: &[Event]) -> io::Result<Counters> {
+            let mut fds = Vec::new();
+            let mut error = None;
+            for &event in events {
+                let leader = fds.first().map(|&(_, fd)| fd).unwrap_or(-1);
+                let mut attr = perf_event_attr::default();
+                attr.size = mem::size_of::<perf_event_attr>() as u32;
+                attr.type_ = perf_type_id_PERF_TYPE_HARDWARE;
+                attr.config = config(event);
+                attr.read_format = (FORMAT_GROUP | FORMAT_TIME_ENABLED | FORMAT_TIME_RUNNING) as u64;
+                attr.set_disabled(if leader == -1 { 1 } else { 0 });
+                attr.set_exclude_kernel(1);
+                attr.set_exclude_hv(1);
+                let fd = unsafe { perf::perf_event_open(&mut attr, 0, -1, leader, 0) };
+                if fd < 0 {
+                    error = Some(io::Error::last_os_error());
+                } else {
+                    fds.push((event, fd));
+                }
+            }
+            if fds.is_empty() {
+                return Err(error
+                    .unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, "no events requested")));
+            }
+            let counters = Counters { fds };
+            if unsafe { perf::ioctls::RESET(counters.leader(), FLAG_GROUP) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(counters)
+        }
+
+        fn leader(&self) -> c_int {
+            self.fds[0].1
+        }
+
+        #[inline]
+        pub fn enable(&self) {
+            unsafe { perf::ioctls::ENABLE(self.leader(), FLAG_GROUP) };
+        }
+
+        #[inline]
+        pub fn disable(&self) {
+            unsafe { perf::ioctls::DISABLE(self.leader(), FLAG_GROUP) };
+        }
+
+        /// Reads the totals accumulated while enabled, scaled up if the kernel
+        /// had to multiplex the counters. Counters it never scheduled stay
+        /// `None`, like unsupported ones.
+        pub fn read(&self) -> io::Result<Counts> {
+            // Layout: nr, time_enabled, time_running, value[nr].
+            let mut buf = vec![0u64; 3 + self.fds.len()];
+            let len = buf.len() * mem::size_of::<u64>();
+            let read = unsafe { libc::read(self.leader(), buf.as_mut_ptr() as *mut _, len) };
+            if read < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let (enabled, running) = (buf[1], buf[2]);
+            let mut counts = Counts::default();
+            for (i, &(event, _)) in self.fds.iter().enumerate() {
+                let raw = buf[3 + i];
+                *counts.slot(event) = if running == 0 {
+                    None
+                } else if running < enabled {
+                    Some((raw as f64 * enabled as f64 / running as f64) as u64)
+                } else {
+                    Some(raw)
+                };
+            }
+            Ok(counts)
+        }
+    }
+
+    impl Drop for Counters {
+        fn drop(&mut self) {
+            for &(_, fd) in self.fds.iter().rev() {
+                unsafe { libc::close(fd) };
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::{Counts, Event};
+    use std::io;
+
+    pub struct Counters;
+
+    impl Counters {
+        pub fn open(_events: &[Event]) -> io::Result<Counters> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "perf_event_open is only available on Linux",
+            ))
+        }
+
+        pub fn enable(&self) {}
+
+        pub fn disable(&self) {}
+
+        pub fn read(&self) -> io::Result<Counts> {
+            Ok(Counts::default())
+        }
+    }
+}
+
+pub use self::sys::Counters;
+
+lazy_static! {
+    static ref TOTALS: Mutex<BTreeMap<(String, String), (u64, Counts)>> =
+        Mutex::new(BTreeMap::new());
+}
+
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Whether counting was requested through `WASM_BENCH_COUNTERS`.
+pub fn enabled() -> bool {
+    env::var_os(ENV_VAR).map_or(false, |value| value != "0" && !value.is_empty())
+}
+
+/// Time a batch counted by [`count`] aims for, in nanoseconds.
+const BATCH_NANOS: u128 = 100_000_000;
+/// Most iterations in a batch.
+const MAX_BATCH: u128 = 100_000;
+
+fn open_counters() -> Option<Counters> {
+    match Counters::open(&Event::ALL) {
+        Ok(counters) => Some(counters),
+        Err(e) => {
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                eprintln!(
+                    "warning: hardware counters unavailable ({}), reporting wall-clock time only",
+                    e
+                );
+            }
+            None
+        }
+    }
+}
+
+/// Counts a batch of iterations of `routine` for `workload`/`runtime`, in a
+/// pass of its own outside criterion's timed loop.
+///
+/// The counters are enabled once around the whole batch, so neither the
+/// timed samples nor the counts include per-iteration syscalls. One untimed
+/// run warms up and sizes the batch to about 100 ms. Every pair is counted
+/// once per process; nothing is done unless counting is enabled.
+pub fn count<O, F: FnMut() -> O>(workload: &str, runtime: &str, mut routine: F) {
+    if !enabled() {
+        return;
+    }
+    let key = (workload.to_string(), runtime.to_string());
+    if TOTALS.lock().unwrap().contains_key(&key) {
+        return;
+    }
+    let counters = match open_counters() {
+        Some(counters) => counters,
+        None => return,
+    };
+
+    let start = Instant::now();
+    routine();
+    let elapsed = start.elapsed().as_nanos().max(1);
+    let iterations = (BATCH_NANOS / elapsed).clamp(1, MAX_BATCH) as u64;
+
+    counters.enable();
+    for _ in 0..iterations {
+        routine();
+    }
+    counters.disable();
+    if let Ok(counts) = counters.read() {
+        TOTALS.lock().unwrap().insert(key, (iterations, counts));
+    }
+}
+
+/// Like [`count`], for a `routine` taking an input `setup` makes before every
+/// iteration. The counters are enabled around every iteration, so the counts
+/// leave the setup out but include the `ioctl`s toggling them.
+pub fn count_with_setup<I, O, S, R>(workload: &str, runtime: &str, mut setup: S, mut routine: R)
+where
+    S: FnMut() -> I,
+    R: FnMut(I) -> O,
+{
+    if !enabled() {
+        return;
+    }
+    let key = (workload.to_string(), runtime.to_string());
+    if TOTALS.lock().unwrap().contains_key(&key) {
+        return;
+    }
+    let counters = match open_counters() {
+        Some(counters) => counters,
+        None => return,
+    };
+
+    let input = setup();
+    let start = Instant::now();
+    let output = routine(input);
+    let elapsed = start.elapsed().as_nanos().max(1);
+    drop(output);
+    let iterations = (BATCH_NANOS / elapsed).clamp(1, MAX_BATCH) as u64;
+
+    for _ in 0..iterations {
+        let input = setup();
+        counters.enable();
+        let output = routine(input);
+        counters.disable();
+        drop(output);
+    }
+    if let Ok(counts) = counters.read() {
+        TOTALS.lock().unwrap().insert(key, (iterations, counts));
+    }
+}
+
+/// Mean counts per iteration of `workload`/`runtime` sampled in this process.
+pub fn per_iteration(workload: &str, runtime: &str) -> Option<Counts> {
+    let totals = TOTALS.lock().unwrap();
+    let &(iterations, ref counts) = totals.get(&(workload.to_string(), runtime.to_string()))?;
+    if iterations == 0 {
+        return None;
+    }
+    let mut mean = Counts::default();
+    for &event in Event::ALL.iter() {
+        *mean.slot(event) = counts.get(event).map(|total| total / iterations);
+    }
+    Some(mean)
+}
+
+/// Mean time per iteration criterion estimated for the benchmark whose output
+/// is in `dir`, e.g. `target/criterion/<workload>/<runtime>`.
+pub(crate) fn criterion_mean_nanos(dir: &Path) -> Option<f64> {
+    let path = dir.join("new").join("estimates.json");
+    let estimates: serde_json::Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    estimates["Mean"]["point_estimate"].as_f64()
+}
+
+/// Prints per-iteration counts next to criterion's mean time for every
+/// workload/runtime pair sampled in this process, and writes them to
+/// `target/wasm-bench/counters.csv`.
+pub fn report(criterion_dir: &Path) -> io::Result<()> {
+    let totals = TOTALS.lock().unwrap();
+    if totals.is_empty() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let mut csv = fs::File::create(Path::new(OUTPUT_DIR).join("counters.csv"))?;
+    write!(csv, "benchmark,backend,mean_nanos")?;
+    for event in Event::ALL.iter() {
+        write!(csv, ",{}", event.name())?;
+    }
+    writeln!(csv)?;
+
+    print!("{:<24}{:<16}{:>14}", "benchmark", "backend", "mean nanos");
+    for event in Event::ALL.iter() {
+        print!("{:>16}", event.name());
+    }
+    println!();
+
+    for ((workload, runtime), (iterations, counts)) in totals.iter() {
+        let mean = criterion_mean_nanos(&criterion_dir.join(workload).join(runtime));
+        let mean_str = mean.map_or("--".to_string(), |m| format!("{:.0}", m));
+        print!("{:<24}{:<16}{:>14}", workload, runtime, mean_str);
+        write!(csv, "{},{},{}", workload, runtime, mean.map_or(String::new(), |m| m.to_string()))?;
+        for &event in Event::ALL.iter() {
+            match counts.get(event) {
+                Some(total) => {
+                    let per_iteration = total / iterations;
+                    print!("{:>16}", per_iteration);
+                    write!(csv, ",{}", per_iteration)?;
+                }
+                None => {
+                    print!("{:>16}", "--");
+                    write!(csv, ",")?;
+                }
+            }
+        }
+        println!();
+        writeln!(csv)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Checks for conditions that make timings unreliable, run before the
+//! harnesses start measuring.
+//!
+//! Problems are printed as warnings. With `WASM_BENCH_STRICT=1` (or
+//! `wasm-bench preflight --strict`) they abort the run instead.
+//! `WASM_BENCH_PIN_CPU=<n>` pins the benchmark thread to CPU `n`.
+
+use std::env;
+use std::fs;
+use std::io;
+
+pub const STRICT_VAR: &str = "WASM_BENCH_STRICT";
+pub const PIN_CPU_VAR: &str = "WASM_BENCH_PIN_CPU";
+
+/// One-minute load average above which the machine is considered busy.
+const MAX_LOAD: f64 = 1.0;
+
+pub struct Issue {
+    pub check: &'static str,
+    pub message: String,
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Parses a kernel CPU list such as `1-3,6`.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        let mut bounds = part.splitn(2, '-').map(|n| n.trim().parse::<usize>());
+        match (bounds.next(), bounds.next()) {
+            (Some(Ok(start)), Some(Ok(end))) => cpus.extend(start..=end),
+            (Some(Ok(cpu)), None) => cpus.push(cpu),
+            _ => {}
+        }
+    }
+    cpus
+}
+
+fn check_governor(issues: &mut Vec<Issue>) {
+    let entries = match fs::read_dir("/sys/devices/system/cpu") {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut governors: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("cpufreq").join("scaling_governor"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|governor| governor.trim().to_string())
+        .filter(|governor| governor != "performance")
+        .collect();
+    governors.sort();
+    governors.dedup();
+    if !governors.is_empty() {
+        issues.push(Issue {
+            check: "governor",
+            message: format!(
+                "CPU frequency governor is {} instead of performance",
+                governors.join(", ")
+            ),
+        });
+    }
+}
+
+fn check_turbo(issues: &mut Vec<Issue>) {
+    let enabled = match read_trimmed("/sys/devices/system/cpu/intel_pstate/no_turbo") {
+        Some(no_turbo) => no_turbo == "0",
+        None => read_trimmed("/sys/devices/system/cpu/cpufreq/boost").map_or(false, |b| b == "1"),
+    };
+    if enabled {
+        issues.push(Issue {
+            check: "turbo",
+            message: "turbo boost is enabled".to_string(),
+        });
+    }
+}
+
+fn check_load(issues: &mut Vec<Issue>) {
+    let load = read_trimmed("/proc/loadavg")
+        .and_then(|loadavg| loadavg.split_whitespace().next()?.parse::<f64>().ok());
+    if let Some(load) = load {
+        if load > MAX_LOAD {
+            issues.push(Issue {
+                check: "load",
+                message: format!("load average is {:.2} (above {:.1})", load, MAX_LOAD),
+            });
+        }
+    }
+}
+
+fn check_debugger(issues: &mut Vec<Issue>) {
+    let tracer = read_trimmed("/proc/self/status").and_then(|status| {
+        status
+            .lines()
+            .find(|line| line.starts_with("TracerPid:"))
+            .map(|line| line["TracerPid:".len()..].trim().to_string())
+    });
+    if let Some(pid) = tracer.filter(|pid| pid != "0") {
+        issues.push(Issue {
+            check: "debugger",
+            message: format!("running under a debugger or tracer (pid {})", pid),
+        });
+    }
+}
+
+fn check_isolation(issues: &mut Vec<Issue>, pin: Option<usize>) {
+    let isolated = match read_trimmed("/sys/devices/system/cpu/isolated") {
+        Some(list) => parse_cpu_list(&list),
+        None => return,
+    };
+    match pin {
+        _ if isolated.is_empty() => issues.push(Issue {
+            check: "isolation",
+            message: "no CPUs are isolated from the scheduler (isolcpus)".to_string(),
+        }),
+        Some(cpu) if !isolated.contains(&cpu) => issues.push(Issue {
+            check: "isolation",
+            message: format!("pinned CPU {} is not one of the isolated CPUs {:?}", cpu, isolated),
+        }),
+        _ => {}
+    }
+}
+
+/// Runs every check. `pin` is the CPU the benchmarks will be pinned to.
+pub fn check(pin: Option<usize>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if cfg!(target_os = "linux") {
+        check_governor(&mut issues);
+        check_turbo(&mut issues);
+        check_load(&mut issues);
+        check_debugger(&mut issues);
+        check_isolation(&mut issues, pin);
+    }
+    issues
+}
+
+/// Pins the calling thread, and the threads it spawns afterwards, to `cpu`.
+#[cfg(target_os = "linux")]
+pub fn pin_to_cpu(cpu: usize) -> io::Result<()> {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CPU {} is out of range", cpu),
+        ));
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_to_cpu(_cpu: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "CPU pinning is only supported on Linux",
+    ))
+}
+
+/// Prints the issues found; in strict mode any issue is an error.
+pub fn run(strict: bool, pin: Option<usize>) -> io::Result<()> {
+    let issues = check(pin);
+    for issue in issues.iter() {
+        eprintln!("warning: {}: {}", issue.check, issue.message);
+    }
+    if strict && !issues.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} pre-flight check(s) failed in strict mode", issues.len()),
+        ));
+    }
+    if let Some(cpu) = pin {
+        pin_to_cpu(cpu)?;
+        eprintln!("pinned to CPU {}", cpu);
+    }
+    Ok(())
+}
+
+/// [`run`] configured from `WASM_BENCH_STRICT` and `WASM_BENCH_PIN_CPU`, for
+/// harnesses whose command line belongs to criterion.
+pub fn run_from_env() -> io::Result<()> {
+    let strict = env::var_os(STRICT_VAR).map_or(false, |value| value != "0" && !value.is_empty());
+    let pin = match env::var(PIN_CPU_VAR) {
+        Ok(cpu) => Some(cpu.trim().parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} must be a CPU number, got {:?}", PIN_CPU_VAR, cpu),
+            )
+        })?),
+        Err(_) => None,
+    };
+    run(strict, pin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("1-3,6\n"), vec![1, 2, 3, 6]);
+        assert_eq!(parse_cpu_list("0"), vec![0]);
+        assert!(parse_cpu_list("\n").is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin_to_cpu_out_of_range() {
+        let error = pin_to_cpu(libc::CPU_SETSIZE as usize).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
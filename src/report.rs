@@ -0,0 +1,223 @@
+//! Result tables, and the reading of criterion's output they are built from.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::results::{Results, Unit};
+use crate::runtime::config::METERED_SUFFIX;
+use crate::stats::{ratio_of_means, Ratio, Summary};
+use crate::workload::NATIVE;
+
+/// Where `cargo bench` leaves criterion's output.
+pub const CRITERION_DIR: &str = "target/criterion";
+
+/// Extracts per-iteration times in nanoseconds from a criterion `raw.csv`.
+///
+/// Both the `sample_time_nanos` column written by criterion 0.2 and the
+/// `sample_measured_value`/`unit` columns of later versions are understood.
+pub fn parse_raw_csv(contents: &str) -> Result<Vec<f64>, String> {
+    let mut lines = contents.lines();
+    let header: Vec<&str> = lines.next().ok_or("empty file")?.split(',').collect();
+    let column = |name: &str| header.iter().position(|&h| h == name);
+    let time = column("sample_time_nanos")
+        .or_else(|| column("sample_measured_value"))
+        .ok_or("no sample time column")?;
+    let iterations = column("iteration_count").ok_or("no iteration_count column")?;
+    let unit = column("unit");
+
+    let mut samples = Vec::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        if let Some(unit) = unit {
+            if fields.get(unit) != Some(&"ns") {
+                return Err(format!("unsupported unit {:?}", fields.get(unit)));
+            }
+        }
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(|| format!("malformed row {:?}", line))
+        };
+        samples.push(field(time)? / field(iterations)?);
+    }
+    Ok(samples)
+}
+
+/// Per-iteration times of the last criterion run of `workload` on `runtime`,
+/// or `None` if it has not been benchmarked.
+pub fn criterion_samples(
+    criterion_dir: &Path,
+    workload: &str,
+    runtime: &str,
+) -> io::Result<Option<Vec<f64>>> {
+    let path = criterion_dir
+        .join(workload)
+        .join(runtime)
+        .join("new")
+        .join("raw.csv");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    parse_raw_csv(&fs::read_to_string(&path)?)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+/// Runtimes criterion has results for under `workload`, native first.
+pub fn benchmarked_runtimes(criterion_dir: &Path, workload: &str) -> io::Result<Vec<String>> {
+    let dir = criterion_dir.join(workload);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut runtimes = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.path().join("new").join("raw.csv").is_file() {
+            runtimes.push(name);
+        }
+    }
+    runtimes.sort_by_key(|name| (name != NATIVE, name.clone()));
+    Ok(runtimes)
+}
+
+pub struct Row {
+    pub workload: String,
+    pub runtime: String,
+    pub summary: Summary,
+    /// Mean time relative to the reference runtime of the same workload;
+    /// `None` for the reference itself or when it was not benchmarked.
+    pub ratio: Option<Ratio>,
+}
+
+/// Summarizes every timed workload/runtime pair in `results`, with times
+/// relative to `reference` (usually [`NATIVE`]).
+pub fn collect(results: &Results, reference: &str) -> Vec<Row> {
+    let timed = || results.results.iter().filter(|e| e.unit == Unit::Nanoseconds);
+    let mut rows = Vec::new();
+    for entry in timed() {
+        let reference = timed().find(|r| r.workload == entry.workload && r.runtime == reference);
+        rows.push(Row {
+            workload: entry.workload.clone(),
+            runtime: entry.runtime.clone(),
+            summary: entry.statistics.clone(),
+            ratio: reference
+                .filter(|r| r.runtime != entry.runtime)
+                .and_then(|r| ratio_of_means(&entry.samples, &r.samples)),
+        });
+    }
+    rows
+}
+
+/// Summarizes the timed pairs of metered runtimes, with times relative to the
+/// same runtime without metering.
+pub fn collect_metering_overhead(results: &Results) -> Vec<Row> {
+    let timed = || results.results.iter().filter(|e| e.unit == Unit::Nanoseconds);
+    let mut rows = Vec::new();
+    for entry in timed().filter(|e| e.runtime.ends_with(METERED_SUFFIX)) {
+        let unmetered = &entry.runtime[..entry.runtime.len() - METERED_SUFFIX.len()];
+        let reference = timed().find(|r| r.workload == entry.workload && r.runtime == unmetered);
+        rows.push(Row {
+            workload: entry.workload.clone(),
+            runtime: entry.runtime.clone(),
+            summary: entry.statistics.clone(),
+            ratio: reference.and_then(|r| ratio_of_means(&entry.samples, &r.samples)),
+        });
+    }
+    rows
+}
+
+/// Formats a ratio with its interval, followed by `~` when the difference is
+/// not statistically significant.
+fn format_ratio(ratio: Option<Ratio>) -> String {
+    match ratio {
+        Some(r) => format!(
+            "{:.2} [{:.2}, {:.2}]{}",
+            r.estimate,
+            r.ci_lower,
+            r.ci_upper,
+            if r.significant() { " " } else { "~" }
+        ),
+        None => "N/A ".to_string(),
+    }
+}
+
+pub fn write_table<W: Write>(out: &mut W, rows: &[Row], reference: &str) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<20}{:<16}{:>14}{:>14}{:>14}{:>32}{:>26}",
+        "benchmark",
+        "backend",
+        "median ns",
+        "mean ns",
+        "stddev ns",
+        "95% CI ns",
+        format!("ratio to {} ", reference)
+    )?;
+    let mut previous: Option<&str> = None;
+    for row in rows {
+        if previous.map_or(false, |workload| workload != row.workload) {
+            writeln!(out)?;
+        }
+        previous = Some(&row.workload);
+        let s = &row.summary;
+        writeln!(
+            out,
+            "{:<20}{:<16}{:>14.1}{:>14.1}{:>14.1}{:>32}{:>26}",
+            row.workload,
+            row.runtime,
+            s.median,
+            s.mean,
+            s.std_dev,
+            format!("[{:.1}, {:.1}]", s.ci_lower, s.ci_upper),
+            if row.runtime == reference {
+                "1.00 ".to_string()
+            } else {
+                format_ratio(row.ratio)
+            },
+        )?;
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Ratios are of mean times, with 95% bootstrap confidence intervals.\n\
+         ~ marks differences that are not statistically significant."
+    )
+}
+
+pub fn print_table(rows: &[Row], reference: &str) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write_table(&mut out, rows, reference)
+}
+
+/// Prints the metering overhead table, if `results` has metered runtimes.
+pub fn print_metering_overhead(results: &Results) -> io::Result<()> {
+    let rows = collect_metering_overhead(results);
+    if rows.is_empty() {
+        return Ok(());
+    }
+    println!();
+    print_table(&rows, "unmetered")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_raw_csv() {
+        let v02 = "group,function,value,sample_time_nanos,iteration_count\n\
+                   fibonacci,wasmer-llvm,,2000.0,2\n\
+                   fibonacci,wasmer-llvm,,6000.0,4\n";
+        assert_eq!(parse_raw_csv(v02), Ok(vec![1000.0, 1500.0]));
+
+        let v03 = "group,function,value,throughput_num,throughput_type,sample_measured_value,unit,iteration_count\n\
+                   sum,wasmi,,,,30.0,ns,3\n";
+        assert_eq!(parse_raw_csv(v03), Ok(vec![10.0]));
+
+        assert!(parse_raw_csv("group,function\n").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! The harness's own result files.
+//!
+//! Results are written as JSON following a versioned schema, so downstream
+//! tools (the report, uploads, comparisons) never read criterion's internal
+//! files. Bump [`SCHEMA_VERSION`] on any incompatible change.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::environment::Environment;
+use crate::lockfile;
+use crate::perf::Counts;
+use crate::report::{benchmarked_runtimes, criterion_samples};
+use crate::stats::Summary;
+use crate::workload::workloads;
+use crate::OUTPUT_DIR;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Where `cargo bench` writes the results of the run.
+pub fn default_path() -> PathBuf {
+    Path::new(OUTPUT_DIR).join("results.json")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unit {
+    /// Wall-clock nanoseconds per iteration.
+    #[serde(rename = "ns")]
+    Nanoseconds,
+    /// Instructions retired per iteration.
+    #[serde(rename = "instructions")]
+    Instructions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub workload: String,
+    pub runtime: String,
+    pub runtime_version: Option<String>,
+    pub runtime_commit: Option<String>,
+    pub unit: Unit,
+    pub statistics: Summary,
+    /// Per-iteration measurements the statistics were computed from.
+    pub samples: Vec<f64>,
+    /// Mean hardware counter values per iteration, when they were collected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters: Option<Counts>,
+}
+
+impl Entry {
+    /// Returns `None` when there are no samples.
+    pub fn new(workload: &str, runtime: &str, unit: Unit, samples: Vec<f64>) -> Option<Entry> {
+        let statistics = Summary::from_samples(&samples)?;
+        let package = lockfile::runtime_package(runtime);
+        Some(Entry {
+            workload: workload.to_string(),
+            runtime: runtime.to_string(),
+            runtime_version: package.as_ref().map(|p| p.version.clone()),
+            runtime_commit: package.as_ref().and_then(|p| p.commit().map(String::from)),
+            unit,
+            statistics,
+            samples,
+            counters: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Results {
+    pub schema_version: u32,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub environment: Environment,
+    pub results: Vec<Entry>,
+}
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+impl Results {
+    pub fn new(results: Vec<Entry>) -> Results {
+        Results {
+            schema_version: SCHEMA_VERSION,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            environment: Environment::current(),
+            results,
+        }
+    }
+
+    /// Converts every pair criterion has results for into harness results.
+    pub fn from_criterion(criterion_dir: &Path) -> io::Result<Results> {
+        let mut pairs = Vec::new();
+        for workload in workloads() {
+            for runtime in benchmarked_runtimes(criterion_dir, workload.name)? {
+                pairs.push((workload.name.to_string(), runtime));
+            }
+        }
+        Results::from_criterion_pairs(criterion_dir, &pairs)
+    }
+
+    /// Converts criterion's results for the given workload/runtime pairs only,
+    /// leaving out whatever earlier runs left in `criterion_dir`.
+    pub fn from_criterion_pairs(
+        criterion_dir: &Path,
+        pairs: &[(String, String)],
+    ) -> io::Result<Results> {
+        let mut entries = Vec::new();
+        for (workload, runtime) in pairs {
+            let samples = match criterion_samples(criterion_dir, workload, runtime)? {
+                Some(samples) => samples,
+                None => continue,
+            };
+            entries.extend(Entry::new(workload, runtime, Unit::Nanoseconds, samples));
+        }
+        Ok(Results::new(entries))
+    }
+
+    pub fn get(&self, workload: &str, runtime: &str) -> Option<&Entry> {
+        self.results
+            .iter()
+            .find(|entry| entry.workload == workload && entry.runtime == runtime)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Results> {
+        let contents = fs::read(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let value: serde_json::Value = serde_json::from_slice(&contents).map_err(invalid_data)?;
+        match value["schema_version"].as_u64() {
+            Some(version) if version == u64::from(SCHEMA_VERSION) => {}
+            version => {
+                return Err(invalid_data(format!(
+                    "{}: unsupported schema version {:?}, expected {}",
+                    path.display(),
+                    version,
+                    SCHEMA_VERSION
+                )))
+            }
+        }
+        serde_json::from_value(value).map_err(invalid_data)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(invalid_data)?;
+        fs::write(path, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut entry = Entry::new("fibonacci", "wasmer-llvm", Unit::Nanoseconds, vec![1.0, 2.0]).unwrap();
+        // Set explicitly, as the version found in Cargo.lock changes with updates.
+        entry.runtime_version = Some("1.2.3".to_string());
+        let results = Results::new(vec![entry]);
+        let path = std::env::temp_dir().join(format!("wasm-bench-results-{}.json", std::process::id()));
+        results.write(&path).unwrap();
+        let read = Results::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.schema_version, SCHEMA_VERSION);
+        let entry = read.get("fibonacci", "wasmer-llvm").unwrap();
+        assert_eq!(entry.unit, Unit::Nanoseconds);
+        assert_eq!(entry.statistics.mean, 1.5);
+        assert_eq!(entry.runtime_version.as_deref(), Some("1.2.3"));
+    }
+}
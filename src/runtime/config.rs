@@ -0,0 +1,180 @@
+//! Compiler settings for benchmarking variants of a runtime.
+//!
+//! Variants are listed in `WASM_BENCH_VARIANTS` as comma-separated
+//! `<runtime>:<setting>:...` specs, e.g.
+//! `wasmtime:O0,wasmer-llvm:cpu=avx2+bmi2,wasmer-clif:unchecked`. Each one is
+//! benchmarked next to the runtimes' default configurations, under the
+//! runtime's name followed by its settings (`wasmtime-O0`). The settings are:
+//!
+//! * `O0` to `O3`: optimization level, or `Os` for speed and size,
+//! * `cpu=<feature>+<feature>...`: CPU features the generated code may use,
+//! * `nan`: NaN canonicalization,
+//! * `checked` / `unchecked`: explicit bounds checks on every memory access,
+//!   or none at all,
+//! * `metered`: gas metering of the executed code.
+//!
+//! Runtimes reject the settings they cannot honour.
+
+use std::env;
+use std::fmt;
+
+pub const VARIANTS_VAR: &str = "WASM_BENCH_VARIANTS";
+
+/// Name suffix of metered variants, which comes last.
+pub const METERED_SUFFIX: &str = "-metered";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    Os,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsChecks {
+    /// Whatever the runtime does by default, usually relying on guard pages.
+    Default,
+    Checked,
+    Unchecked,
+}
+
+impl Default for BoundsChecks {
+    fn default() -> BoundsChecks {
+        BoundsChecks::Default
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub opt_level: Option<OptLevel>,
+    pub cpu_features: Vec<String>,
+    pub nan_canonicalization: bool,
+    pub bounds_checks: BoundsChecks,
+    pub metering: bool,
+}
+
+impl Config {
+    /// The settings in canonical order, as given in a variant spec.
+    fn settings(&self) -> Vec<String> {
+        let mut settings = Vec::new();
+        if let Some(opt_level) = self.opt_level {
+            settings.push(format!("{:?}", opt_level));
+        }
+        if !self.cpu_features.is_empty() {
+            settings.push(format!("cpu={}", self.cpu_features.join("+")));
+        }
+        if self.nan_canonicalization {
+            settings.push("nan".to_string());
+        }
+        match self.bounds_checks {
+            BoundsChecks::Default => {}
+            BoundsChecks::Checked => settings.push("checked".to_string()),
+            BoundsChecks::Unchecked => settings.push("unchecked".to_string()),
+        }
+        if self.metering {
+            settings.push(METERED_SUFFIX[1..].to_string());
+        }
+        settings
+    }
+
+    /// Appended to the runtime's name, e.g. `-O3-cpu=avx2` (empty for the
+    /// default configuration).
+    pub fn suffix(&self) -> String {
+        self.settings()
+            .iter()
+            .map(|setting| format!("-{}", setting))
+            .collect()
+    }
+
+    fn set(&mut self, setting: &str) -> Result<(), String> {
+        match setting {
+            "O0" => self.opt_level = Some(OptLevel::O0),
+            "O1" => self.opt_level = Some(OptLevel::O1),
+            "O2" => self.opt_level = Some(OptLevel::O2),
+            "O3" => self.opt_level = Some(OptLevel::O3),
+            "Os" => self.opt_level = Some(OptLevel::Os),
+            "nan" => self.nan_canonicalization = true,
+            "checked" => self.bounds_checks = BoundsChecks::Checked,
+            "unchecked" => self.bounds_checks = BoundsChecks::Unchecked,
+            "metered" => self.metering = true,
+            _ if setting.starts_with("cpu=") => {
+                self.cpu_features = setting["cpu=".len()..]
+                    .split('+')
+                    .filter(|feature| !feature.is_empty())
+                    .map(|feature| feature.to_string())
+                    .collect();
+            }
+            _ => return Err(format!("unknown compiler setting {:?}", setting)),
+        }
+        Ok(())
+    }
+}
+
+/// A runtime, by name, with non-default compiler settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub runtime: String,
+    pub config: Config,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.runtime, self.config.suffix())
+    }
+}
+
+pub fn parse(specs: &str) -> Result<Vec<Variant>, String> {
+    specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| {
+            let mut parts = spec.split(':');
+            let runtime = parts.next().unwrap_or_default().to_string();
+            let mut config = Config::default();
+            for setting in parts {
+                config
+                    .set(setting.trim())
+                    .map_err(|e| format!("{} in variant {}", e, spec))?;
+            }
+            if config == Config::default() {
+                return Err(format!("variant {} has no compiler settings", spec));
+            }
+            Ok(Variant { runtime, config })
+        })
+        .collect()
+}
+
+pub fn from_env() -> Result<Vec<Variant>, String> {
+    match env::var(VARIANTS_VAR) {
+        Ok(specs) => parse(&specs).map_err(|e| format!("{}: {}", VARIANTS_VAR, e)),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let variants =
+            parse("wasmer-llvm:O1, wasmer-llvm:unchecked:cpu=avx2+bmi2:O3,wasmtime:metered:nan")
+                .unwrap();
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants[0].to_string(), "wasmer-llvm-O1");
+        assert_eq!(variants[1].config.opt_level, Some(OptLevel::O3));
+        assert_eq!(variants[1].config.cpu_features, vec!["avx2", "bmi2"]);
+        assert_eq!(
+            variants[1].to_string(),
+            "wasmer-llvm-O3-cpu=avx2+bmi2-unchecked"
+        );
+        assert_eq!(variants[2].to_string(), "wasmtime-nan-metered");
+
+        assert_eq!(parse("wasmtime:Os").unwrap()[0].to_string(), "wasmtime-Os");
+        assert!(parse("wasmer-llvm").is_err());
+        assert!(parse("wasmer-llvm:O4").is_err());
+    }
+}
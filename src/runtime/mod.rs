@@ -0,0 +1,249 @@
+//! The WebAssembly runtimes under comparison.
+//!
+//! Every runtime implements [`Runtime`] so the criterion benchmarks and the
+//! instruction-count harness drive them the same way.
+
+use std::error;
+use std::fmt;
+use std::path::Path;
+
+pub use self::config::Config;
+
+pub mod config;
+#[cfg(feature = "v8")]
+pub mod v8;
+#[cfg(any(
+    feature = "backend-cranelift",
+    feature = "backend-llvm",
+    feature = "backend-singlepass"
+))]
+pub mod wasmer;
+#[cfg(feature = "bench-wasmi")]
+pub mod wasmi;
+#[cfg(feature = "bench-wasmtime")]
+pub mod wasmtime;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Compile(String),
+    Instantiate(String),
+    Resolve(String),
+    Call(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Compile(msg) => write!(f, "compile error: {}", msg),
+            Error::Instantiate(msg) => write!(f, "instantiation error: {}", msg),
+            Error::Resolve(msg) => write!(f, "export resolution error: {}", msg),
+            Error::Call(msg) => write!(f, "call error: {}", msg),
+            Error::Unsupported(msg) => write!(f, "unsupported: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// A call to an exported function with fixed arguments.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub export: &'static str,
+    pub args: Vec<Value>,
+    pub result: Option<Type>,
+}
+
+/// A call resolved against an instance, ready to be run in a measurement loop.
+pub type Prepared<'a> = Box<dyn FnMut() -> Result<Option<Value>, Error> + 'a>;
+
+/// Creates a fresh instance on every call and returns the operation to run
+/// once on it, for workloads whose runs must not share any state.
+pub type Setup<'a> = Box<dyn FnMut() -> Result<Prepared<'a>, Error> + 'a>;
+
+pub trait Runtime {
+    /// Name used for benchmark ids and reports, e.g. `wasmer-llvm`.
+    fn name(&self) -> &str;
+
+    /// Whether the compile and instantiate benchmarks are run for this
+    /// runtime.
+    fn benches_compile(&self) -> bool {
+        false
+    }
+
+    /// Compiles `wasm` and drops the result.
+    fn compile(&self, wasm: &[u8]) -> Result<(), Error> {
+        let _ = wasm;
+        Err(Error::Unsupported(format!(
+            "{} does not expose ahead-of-time compilation",
+            self.name()
+        )))
+    }
+
+    /// Compiles and instantiates `wasm` without imports.
+    fn instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Box<dyn Instance + 'a>, Error>;
+
+    /// Compiles `wasm` once, ready to instantiate it without imports, and drop
+    /// the instance, repeatedly.
+    fn prepare_instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Prepared<'a>, Error> {
+        let _ = wasm;
+        Err(Error::Unsupported(format!(
+            "{} does not expose ahead-of-time compilation",
+            self.name()
+        )))
+    }
+
+    /// Whether the WASI workloads are run on this runtime.
+    fn supports_wasi(&self) -> bool {
+        false
+    }
+
+    /// Compiles and instantiates `wasm` with WASI imports, preopening `dir`
+    /// as `/sandbox`.
+    fn instantiate_wasi<'a>(
+        &'a self,
+        wasm: &[u8],
+        dir: &Path,
+    ) -> Result<Box<dyn Instance + 'a>, Error> {
+        let _ = (wasm, dir);
+        Err(Error::Unsupported(format!(
+            "{} does not implement WASI",
+            self.name()
+        )))
+    }
+
+    /// Whether Emscripten programs are run on this runtime.
+    fn supports_emscripten(&self) -> bool {
+        false
+    }
+
+    /// Compiles the Emscripten program `wasm`, ready to instantiate it with
+    /// the Emscripten imports and run its `main` with `args` (after the
+    /// program name `name`) once per instance.
+    fn prepare_emscripten<'a>(
+        &'a self,
+        wasm: &[u8],
+        name: &str,
+        args: &[&str],
+    ) -> Result<Setup<'a>, Error> {
+        let _ = (wasm, name, args);
+        Err(Error::Unsupported(format!(
+            "{} does not implement the Emscripten imports",
+            self.name()
+        )))
+    }
+
+    /// Whether the threads workloads are run on this runtime.
+    fn supports_threads(&self) -> bool {
+        false
+    }
+
+    /// Compiles `wasm` and instantiates it `count` times, every instance
+    /// importing the same shared memory, as declared by its `env.memory`
+    /// import. The instances can be called from different threads.
+    fn instantiate_shared<'a>(
+        &'a self,
+        wasm: &[u8],
+        count: usize,
+    ) -> Result<Vec<Box<dyn Instance + Send + 'a>>, Error> {
+        let _ = (wasm, count);
+        Err(Error::Unsupported(format!(
+            "{} does not implement shared memories",
+            self.name()
+        )))
+    }
+
+    /// This runtime with non-default compiler settings.
+    fn with_config(&self, config: &Config) -> Result<Box<dyn Runtime>, Error> {
+        let _ = config;
+        Err(Error::Unsupported(format!(
+            "{} has no configurable compiler settings",
+            self.name()
+        )))
+    }
+}
+
+pub trait Instance {
+    /// Calls `export` once. Meant for setup, not for measurement loops.
+    fn call(&self, export: &str, args: &[Value]) -> Result<Vec<Value>, Error>;
+
+    /// Resolves `call` once so it can be invoked repeatedly with the least
+    /// overhead the runtime's API allows.
+    fn prepare<'a>(&'a self, call: &Call) -> Result<Prepared<'a>, Error>;
+}
+
+/// The runtimes enabled in this build with their default settings.
+fn default_runtimes() -> Vec<Box<dyn Runtime>> {
+    #[allow(unused_mut)]
+    let mut runtimes: Vec<Box<dyn Runtime>> = Vec::new();
+    #[cfg(any(
+        feature = "backend-cranelift",
+        feature = "backend-llvm",
+        feature = "backend-singlepass"
+    ))]
+    runtimes.extend(
+        wasmer::Backend::ALL
+            .iter()
+            .map(|&backend| Box::new(wasmer::Wasmer::new(backend)) as Box<dyn Runtime>),
+    );
+    #[cfg(all(
+        feature = "metering",
+        any(feature = "backend-llvm", feature = "backend-singlepass")
+    ))]
+    runtimes.extend(
+        wasmer::Backend::ALL
+            .iter()
+            .filter(|backend| backend.supports_metering())
+            .map(|&backend| {
+                let config = Config {
+                    metering: true,
+                    ..Config::default()
+                };
+                Box::new(wasmer::Wasmer::with_config(backend, &config).unwrap()) as Box<dyn Runtime>
+            }),
+    );
+    #[cfg(feature = "bench-wasmi")]
+    runtimes.push(Box::new(self::wasmi::Wasmi));
+    #[cfg(feature = "bench-wasmtime")]
+    runtimes.push(Box::new(self::wasmtime::Wasmtime::default()));
+    #[cfg(feature = "v8")]
+    runtimes.push(Box::new(self::v8::V8::new()));
+    runtimes
+}
+
+/// All runtimes enabled in this build, followed by the variants listed in
+/// `WASM_BENCH_VARIANTS`, in report order.
+pub fn try_runtimes() -> Result<Vec<Box<dyn Runtime>>, String> {
+    let mut runtimes = default_runtimes();
+    for variant in config::from_env()? {
+        let runtime = runtimes
+            .iter()
+            .find(|runtime| runtime.name() == variant.runtime)
+            .ok_or_else(|| format!("variant {}: unknown runtime {}", variant, variant.runtime))?
+            .with_config(&variant.config)
+            .map_err(|e| format!("variant {}: {}", variant, e))?;
+        runtimes.push(runtime);
+    }
+    Ok(runtimes)
+}
+
+/// Like [`try_runtimes`], for harnesses that already checked the variants.
+pub fn runtimes() -> Vec<Box<dyn Runtime>> {
+    try_runtimes().unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,184 @@
+use super::{Call, Error, Instance, Prepared, Runtime, Type, Value};
+
+use rust_wasm_c_api::*;
+use std::mem;
+use std::slice;
+use std::str;
+
+/// V8 through the wasm-c-api. One engine and store are shared by all the
+/// instances created from a runtime.
+pub struct V8 {
+    engine: *mut wasm_engine_t,
+    store: *mut wasm_store_t,
+}
+
+impl V8 {
+    pub fn new() -> V8 {
+        unsafe {
+            let engine = wasm_engine_new();
+            let store = wasm_store_new(engine);
+            V8 { engine, store }
+        }
+    }
+}
+
+impl Drop for V8 {
+    fn drop(&mut self) {
+        unsafe {
+            wasm_store_delete(self.store);
+            wasm_engine_delete(self.engine);
+        }
+    }
+}
+
+impl Runtime for V8 {
+    fn name(&self) -> &str {
+        "wasm-c-api-v8"
+    }
+
+    fn instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Box<dyn Instance + 'a>, Error> {
+        unsafe {
+            let mut byte_vec = wasm.to_vec();
+            let bytes = wasm_byte_vec_t {
+                size: byte_vec.len(),
+                data: byte_vec.as_mut_ptr() as _,
+            };
+            let module = wasm_module_new(self.store, &bytes as *const wasm_byte_vec_t);
+            if module.is_null() {
+                return Err(Error::Compile("wasm_module_new failed".to_string()));
+            }
+            let imports = &[];
+            let instance = wasm_instance_new(self.store, module, imports.as_ptr());
+            if instance.is_null() {
+                wasm_module_delete(module);
+                return Err(Error::Instantiate("wasm_instance_new failed".to_string()));
+            }
+
+            let mut export_types: wasm_exporttype_vec_t = mem::zeroed();
+            wasm_module_exports(module, &mut export_types as *mut wasm_exporttype_vec_t);
+            let mut exports: wasm_extern_vec_t = mem::zeroed();
+            wasm_instance_exports(instance, &mut exports as *mut wasm_extern_vec_t);
+
+            Ok(Box::new(V8Instance {
+                module,
+                instance,
+                exports,
+                export_types,
+            }))
+        }
+    }
+}
+
+struct V8Instance {
+    module: *mut wasm_module_t,
+    instance: *mut wasm_instance_t,
+    exports: wasm_extern_vec_t,
+    export_types: wasm_exporttype_vec_t,
+}
+
+impl Drop for V8Instance {
+    fn drop(&mut self) {
+        unsafe {
+            wasm_extern_vec_delete(&mut self.exports as *mut wasm_extern_vec_t);
+            wasm_exporttype_vec_delete(&mut self.export_types as *mut wasm_exporttype_vec_t);
+            wasm_instance_delete(self.instance);
+            wasm_module_delete(self.module);
+        }
+    }
+}
+
+impl V8Instance {
+    unsafe fn func(&self, export_name: &str) -> Result<*const wasm_func_t, Error> {
+        let export_types =
+            slice::from_raw_parts(self.export_types.data, self.export_types.size);
+        let index = export_types.iter().position(|&export_type| {
+            let wasm_name = wasm_exporttype_name(export_type);
+            let name_bytes: &[u8] =
+                slice::from_raw_parts((*wasm_name).data as *const u8, (*wasm_name).size);
+            str::from_utf8_unchecked(name_bytes) == export_name
+        });
+        let index = index
+            .ok_or_else(|| Error::Resolve(format!("export {} not found", export_name)))?;
+        let exports = slice::from_raw_parts(self.exports.data, self.exports.size);
+        let func = wasm_extern_as_func(exports[index]);
+        if func.is_null() {
+            return Err(Error::Resolve(format!("export {} is not a function", export_name)));
+        }
+        Ok(func)
+    }
+}
+
+fn to_v8(value: &Value) -> wasm_val_t {
+    match *value {
+        Value::I32(v) => wasm_val_t {
+            kind: wasm_valkind_t_WASM_I32,
+            of: wasm_val_t__bindgen_ty_1 { i32: v },
+        },
+        Value::I64(v) => wasm_val_t {
+            kind: wasm_valkind_t_WASM_I64,
+            of: wasm_val_t__bindgen_ty_1 { i64: v },
+        },
+        Value::F32(v) => wasm_val_t {
+            kind: wasm_valkind_t_WASM_F32,
+            of: wasm_val_t__bindgen_ty_1 { f32: v },
+        },
+        Value::F64(v) => wasm_val_t {
+            kind: wasm_valkind_t_WASM_F64,
+            of: wasm_val_t__bindgen_ty_1 { f64: v },
+        },
+    }
+}
+
+fn zero(ty: Type) -> Value {
+    match ty {
+        Type::I32 => Value::I32(0),
+        Type::I64 => Value::I64(0),
+        Type::F32 => Value::F32(0.0),
+        Type::F64 => Value::F64(0.0),
+    }
+}
+
+unsafe fn from_v8(value: &wasm_val_t) -> Value {
+    match value.kind {
+        k if k == wasm_valkind_t_WASM_I64 => Value::I64(value.of.i64),
+        k if k == wasm_valkind_t_WASM_F32 => Value::F32(value.of.f32),
+        k if k == wasm_valkind_t_WASM_F64 => Value::F64(value.of.f64),
+        _ => Value::I32(value.of.i32),
+    }
+}
+
+unsafe fn call_func(
+    func: *const wasm_func_t,
+    args: &[wasm_val_t],
+    results: &mut [wasm_val_t],
+) -> Result<(), Error> {
+    let trap = wasm_func_call(func, args.as_ptr(), results.as_mut_ptr());
+    if trap.is_null() {
+        Ok(())
+    } else {
+        wasm_trap_delete(trap);
+        Err(Error::Call("wasm trap".to_string()))
+    }
+}
+
+impl Instance for V8Instance {
+    fn call(&self, export: &str, args: &[Value]) -> Result<Vec<Value>, Error> {
+        unsafe {
+            let func = self.func(export)?;
+            let args: Vec<wasm_val_t> = args.iter().map(to_v8).collect();
+            let mut results = vec![to_v8(&Value::I64(0)); wasm_func_result_arity(func)];
+            call_func(func, &args, &mut results)?;
+            Ok(results.iter().map(|r| from_v8(r)).collect())
+        }
+    }
+
+    fn prepare<'a>(&'a self, call: &Call) -> Result<Prepared<'a>, Error> {
+        let func = unsafe { self.func(call.export)? };
+        let args: Vec<wasm_val_t> = call.args.iter().map(to_v8).collect();
+        let mut results: Vec<wasm_val_t> = call.result.map(zero).iter().map(to_v8).collect();
+        Ok(Box::new(move || unsafe {
+            call_func(func, &args, &mut results)?;
+            Ok(results.first().map(|r| from_v8(r)))
+        }))
+    }
+}
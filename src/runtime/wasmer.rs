@@ -0,0 +1,472 @@
+#[cfg(feature = "emscripten")]
+use std::io::{self, Write};
+#[cfg(feature = "emscripten")]
+use std::rc::Rc;
+#[cfg(feature = "wasi")]
+use std::env;
+#[cfg(feature = "wasi")]
+use std::path::Path;
+
+#[cfg(feature = "wasi")]
+use wasm_bench_benchmarks::wasi::SANDBOX;
+
+use super::config::{BoundsChecks, Config};
+#[cfg(feature = "emscripten")]
+use super::Setup;
+use super::{Call, Error, Instance, Prepared, Runtime, Type, Value};
+#[cfg(feature = "wasi")]
+use crate::WASI_WASM;
+
+#[cfg(feature = "backend-cranelift")]
+use wasmer_clif_backend::CraneliftCompiler;
+#[cfg(feature = "emscripten")]
+use wasmer_emscripten::EmscriptenGlobals;
+#[cfg(feature = "backend-llvm")]
+use wasmer_llvm_backend::LLVMCompiler;
+use wasmer_runtime_core::backend::{CompilerConfig, Features, MemoryBoundCheckMode};
+use wasmer_runtime_core::{import::ImportObject, types, Func, Module};
+#[cfg(feature = "backend-singlepass")]
+use wasmer_singlepass_backend::SinglePassCompiler;
+
+/// The wasmer backends enabled through `backend-*` features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    #[cfg(feature = "backend-cranelift")]
+    Cranelift,
+    #[cfg(feature = "backend-llvm")]
+    LLVM,
+    #[cfg(feature = "backend-singlepass")]
+    Singlepass,
+}
+
+impl Backend {
+    pub const ALL: &'static [Backend] = &[
+        #[cfg(feature = "backend-cranelift")]
+        Backend::Cranelift,
+        #[cfg(feature = "backend-llvm")]
+        Backend::LLVM,
+        #[cfg(feature = "backend-singlepass")]
+        Backend::Singlepass,
+    ];
+
+    /// Whether the backend can run the metering middleware. Cranelift does
+    /// not support middlewares.
+    pub fn supports_metering(self) -> bool {
+        match self {
+            #[cfg(feature = "backend-cranelift")]
+            Backend::Cranelift => false,
+            #[cfg(feature = "backend-llvm")]
+            Backend::LLVM => true,
+            #[cfg(feature = "backend-singlepass")]
+            Backend::Singlepass => true,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "backend-cranelift")]
+            Backend::Cranelift => "wasmer-clif",
+            #[cfg(feature = "backend-llvm")]
+            Backend::LLVM => "wasmer-llvm",
+            #[cfg(feature = "backend-singlepass")]
+            Backend::Singlepass => "wasmer-dynasm",
+        }
+    }
+}
+
+/// Compilers running wasmer's gas metering middleware.
+#[cfg(feature = "metering")]
+#[cfg_attr(
+    not(any(feature = "backend-llvm", feature = "backend-singlepass")),
+    allow(unused)
+)]
+mod metered {
+    use super::Backend;
+    use wasmer_middleware_common::metering::Metering;
+    use wasmer_runtime_core::backend::CompilerConfig;
+    use wasmer_runtime_core::codegen::{MiddlewareChain, StreamingCompiler};
+    use wasmer_runtime_core::{error::CompileResult, Module};
+
+    /// Gas limit of metered modules, high enough never to be reached.
+    const GAS_LIMIT: u64 = u64::max_value();
+
+    fn chain() -> MiddlewareChain {
+        let mut chain = MiddlewareChain::new();
+        chain.push(Metering::new(GAS_LIMIT));
+        chain
+    }
+
+    pub fn compile(backend: Backend, wasm: &[u8], config: CompilerConfig) -> CompileResult<Module> {
+        match backend {
+            #[cfg(feature = "backend-cranelift")]
+            Backend::Cranelift => unreachable!("Cranelift does not support middlewares"),
+            #[cfg(feature = "backend-llvm")]
+            Backend::LLVM => {
+                let compiler: StreamingCompiler<
+                    wasmer_llvm_backend::ModuleCodeGenerator,
+                    _,
+                    _,
+                    _,
+                    _,
+                > = StreamingCompiler::new(chain);
+                wasmer_runtime_core::compile_with_config(wasm, &compiler, config)
+            }
+            #[cfg(feature = "backend-singlepass")]
+            Backend::Singlepass => {
+                let compiler: StreamingCompiler<
+                    wasmer_singlepass_backend::ModuleCodeGenerator,
+                    _,
+                    _,
+                    _,
+                    _,
+                > = StreamingCompiler::new(chain);
+                wasmer_runtime_core::compile_with_config(wasm, &compiler, config)
+            }
+        }
+    }
+}
+
+pub struct Wasmer {
+    backend: Backend,
+    config: Config,
+    name: String,
+}
+
+impl Wasmer {
+    pub fn new(backend: Backend) -> Wasmer {
+        Wasmer {
+            backend,
+            config: Config::default(),
+            name: backend.name().to_string(),
+        }
+    }
+
+    /// `backend` with the settings of `config` that wasmer's `CompilerConfig`
+    /// exposes. Optimization levels and NaN canonicalization are fixed by the
+    /// backends.
+    pub fn with_config(backend: Backend, config: &Config) -> Result<Wasmer, Error> {
+        let unsupported = |setting: &str| {
+            Err(Error::Unsupported(format!(
+                "{} has no {} setting",
+                backend.name(),
+                setting
+            )))
+        };
+        if config.opt_level.is_some() {
+            return unsupported("optimization level");
+        }
+        if config.nan_canonicalization {
+            return unsupported("NaN canonicalization");
+        }
+        #[cfg(feature = "backend-llvm")]
+        let cpu_features_supported = backend == Backend::LLVM;
+        #[cfg(not(feature = "backend-llvm"))]
+        let cpu_features_supported = false;
+        if !config.cpu_features.is_empty() && !cpu_features_supported {
+            return unsupported("CPU features");
+        }
+        if config.metering && !(cfg!(feature = "metering") && backend.supports_metering()) {
+            return unsupported("metering");
+        }
+        Ok(Wasmer {
+            backend,
+            config: config.clone(),
+            name: format!("{}{}", backend.name(), config.suffix()),
+        })
+    }
+
+    fn compiler_config(&self) -> CompilerConfig {
+        let cpu_features = if self.config.cpu_features.is_empty() {
+            None
+        } else {
+            let features: Vec<String> = self
+                .config
+                .cpu_features
+                .iter()
+                .map(|feature| format!("+{}", feature))
+                .collect();
+            Some(features.join(","))
+        };
+        CompilerConfig {
+            memory_bound_check_mode: match self.config.bounds_checks {
+                BoundsChecks::Default => MemoryBoundCheckMode::Default,
+                BoundsChecks::Checked => MemoryBoundCheckMode::Enable,
+                BoundsChecks::Unchecked => MemoryBoundCheckMode::Disable,
+            },
+            cpu_features,
+            // Backends without SIMD support still reject modules using it.
+            features: Features {
+                simd: true,
+                threads: false,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn compile_module(&self, wasm: &[u8]) -> Result<Module, Error> {
+        let config = self.compiler_config();
+        #[cfg(feature = "metering")]
+        {
+            if self.config.metering {
+                return metered::compile(self.backend, wasm, config)
+                    .map_err(|e| Error::Compile(format!("{:?}", e)));
+            }
+        }
+        match self.backend {
+            #[cfg(feature = "backend-cranelift")]
+            Backend::Cranelift => {
+                wasmer_runtime_core::compile_with_config(wasm, &CraneliftCompiler::new(), config)
+            }
+            #[cfg(feature = "backend-llvm")]
+            Backend::LLVM => {
+                wasmer_runtime_core::compile_with_config(wasm, &LLVMCompiler::new(), config)
+            }
+            #[cfg(feature = "backend-singlepass")]
+            Backend::Singlepass => {
+                wasmer_runtime_core::compile_with_config(wasm, &SinglePassCompiler::new(), config)
+            }
+        }
+        .map_err(|e| Error::Compile(format!("{:?}", e)))
+    }
+}
+
+impl Runtime for Wasmer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn benches_compile(&self) -> bool {
+        true
+    }
+
+    fn compile(&self, wasm: &[u8]) -> Result<(), Error> {
+        self.compile_module(wasm).map(|_| ())
+    }
+
+    fn instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Box<dyn Instance + 'a>, Error> {
+        instantiate_module(&self.compile_module(wasm)?, &ImportObject::new())
+    }
+
+    fn prepare_instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Prepared<'a>, Error> {
+        let module = self.compile_module(wasm)?;
+        let imports = ImportObject::new();
+        Ok(Box::new(move || {
+            module
+                .instantiate(&imports)
+                .map(|_| None)
+                .map_err(|e| Error::Instantiate(format!("{:?}", e)))
+        }))
+    }
+
+    /// Whether `wasmer-wasi` provides the WASI version the `benchmarks`
+    /// crate was built against, checked by instantiating it.
+    #[cfg(feature = "wasi")]
+    fn supports_wasi(&self) -> bool {
+        self.instantiate_wasi(WASI_WASM, &env::temp_dir()).is_ok()
+    }
+
+    fn supports_emscripten(&self) -> bool {
+        cfg!(feature = "emscripten")
+    }
+
+    #[cfg(feature = "emscripten")]
+    fn prepare_emscripten<'a>(
+        &'a self,
+        wasm: &[u8],
+        name: &str,
+        args: &[&str],
+    ) -> Result<Setup<'a>, Error> {
+        let module = Rc::new(self.compile_module(wasm)?);
+        let name = Rc::new(name.to_string());
+        let args: Rc<Vec<String>> = Rc::new(args.iter().map(|arg| arg.to_string()).collect());
+        Ok(Box::new(move || {
+            // The imports point into the globals, which are boxed so they stay
+            // put when moved into the closure.
+            let mut globals =
+                Box::new(EmscriptenGlobals::new(&module).map_err(Error::Instantiate)?);
+            let imports = wasmer_emscripten::generate_emscripten_env(&mut globals);
+            let mut instance = module
+                .instantiate(&imports)
+                .map_err(|e| Error::Instantiate(format!("{:?}", e)))?;
+            let silence = Silence::stdout().map_err(|e| {
+                Error::Instantiate(format!("cannot redirect the standard output: {}", e))
+            })?;
+            let (module, name, args) = (Rc::clone(&module), Rc::clone(&name), Rc::clone(&args));
+            let run: Prepared<'a> = Box::new(move || {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                silence.during(|| {
+                    wasmer_emscripten::run_emscripten_instance(
+                        &module,
+                        &mut instance,
+                        &mut globals,
+                        &name,
+                        args,
+                        None,
+                        vec![],
+                    )
+                })
+                .map(|()| None)
+                .map_err(call_error)
+            });
+            Ok(run)
+        }))
+    }
+
+    #[cfg(feature = "wasi")]
+    fn instantiate_wasi<'a>(
+        &'a self,
+        wasm: &[u8],
+        dir: &Path,
+    ) -> Result<Box<dyn Instance + 'a>, Error> {
+        let module = self.compile_module(wasm)?;
+        let version = wasmer_wasi::get_wasi_version(&module)
+            .ok_or_else(|| Error::Instantiate("module does not import WASI".to_string()))?;
+        let imports = wasmer_wasi::generate_import_object_for_version(
+            version,
+            vec![],
+            vec![],
+            vec![],
+            vec![(SANDBOX.to_string(), dir.to_path_buf())],
+        );
+        instantiate_module(&module, &imports)
+    }
+
+    fn with_config(&self, config: &Config) -> Result<Box<dyn Runtime>, Error> {
+        Ok(Box::new(Wasmer::with_config(self.backend, config)?))
+    }
+}
+
+/// Sends the standard output to `/dev/null` during the runs of Emscripten
+/// programs, whose output wasmer writes to the process' descriptors, so it
+/// does not end up in the reports while the harness' own output still does.
+/// The descriptors are opened beforehand, leaving one `dup2` each way around
+/// a run.
+#[cfg(feature = "emscripten")]
+struct Silence {
+    null: libc::c_int,
+    saved: libc::c_int,
+}
+
+#[cfg(feature = "emscripten")]
+impl Silence {
+    fn stdout() -> io::Result<Silence> {
+        io::stdout().flush()?;
+        unsafe {
+            let null = libc::open(b"/dev/null\0".as_ptr() as *const _, libc::O_WRONLY);
+            if null < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let saved = libc::dup(libc::STDOUT_FILENO);
+            if saved < 0 {
+                let error = io::Error::last_os_error();
+                libc::close(null);
+                return Err(error);
+            }
+            Ok(Silence { null, saved })
+        }
+    }
+
+    /// Runs `f` with the standard output sent to `/dev/null`.
+    fn during<T>(&self, f: impl FnOnce() -> T) -> T {
+        unsafe { libc::dup2(self.null, libc::STDOUT_FILENO) };
+        let result = f();
+        unsafe { libc::dup2(self.saved, libc::STDOUT_FILENO) };
+        result
+    }
+}
+
+#[cfg(feature = "emscripten")]
+impl Drop for Silence {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.null);
+            libc::close(self.saved);
+        }
+    }
+}
+
+struct WasmerInstance(wasmer_runtime_core::Instance);
+
+fn instantiate_module(module: &Module, imports: &ImportObject) -> Result<Box<dyn Instance>, Error> {
+    let instance = module
+        .instantiate(imports)
+        .map_err(|e| Error::Instantiate(format!("{:?}", e)))?;
+    Ok(Box::new(WasmerInstance(instance)))
+}
+
+fn to_wasmer(value: &Value) -> types::Value {
+    match *value {
+        Value::I32(v) => types::Value::I32(v),
+        Value::I64(v) => types::Value::I64(v),
+        Value::F32(v) => types::Value::F32(v),
+        Value::F64(v) => types::Value::F64(v),
+    }
+}
+
+fn from_wasmer(value: &types::Value) -> Result<Value, Error> {
+    match *value {
+        types::Value::I32(v) => Ok(Value::I32(v)),
+        types::Value::I64(v) => Ok(Value::I64(v)),
+        types::Value::F32(v) => Ok(Value::F32(v)),
+        types::Value::F64(v) => Ok(Value::F64(v)),
+        ref other => Err(Error::Unsupported(format!("result value {:?}", other))),
+    }
+}
+
+fn resolve_error<E: std::fmt::Debug>(e: E) -> Error {
+    Error::Resolve(format!("{:?}", e))
+}
+
+fn call_error<E: std::fmt::Debug>(e: E) -> Error {
+    Error::Call(format!("{:?}", e))
+}
+
+impl Instance for WasmerInstance {
+    fn call(&self, export: &str, args: &[Value]) -> Result<Vec<Value>, Error> {
+        let func = self.0.dyn_func(export).map_err(resolve_error)?;
+        let args: Vec<types::Value> = args.iter().map(to_wasmer).collect();
+        let results = func.call(&args).map_err(call_error)?;
+        results.iter().map(from_wasmer).collect()
+    }
+
+    fn prepare<'a>(&'a self, call: &Call) -> Result<Prepared<'a>, Error> {
+        let export = call.export;
+        // Signatures used by the workloads go through typed `Func`s, which is
+        // how embedders call wasmer; anything else falls back to `DynFunc`.
+        let prepared: Prepared<'a> = match (call.args.as_slice(), call.result) {
+            (&[], None) => {
+                let func: Func<()> = self.0.func(export).map_err(resolve_error)?;
+                Box::new(move || func.call().map(|()| None).map_err(call_error))
+            }
+            (&[Value::I32(a)], None) => {
+                let func: Func<i32> = self.0.func(export).map_err(resolve_error)?;
+                Box::new(move || func.call(a).map(|()| None).map_err(call_error))
+            }
+            (&[Value::I64(a)], Some(Type::I64)) => {
+                let func: Func<i64, i64> = self.0.func(export).map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(a)
+                        .map(|r| Some(Value::I64(r)))
+                        .map_err(call_error)
+                })
+            }
+            (&[Value::I32(a), Value::I32(b)], Some(Type::I32)) => {
+                let func: Func<(i32, i32), i32> = self.0.func(export).map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(a, b)
+                        .map(|r| Some(Value::I32(r)))
+                        .map_err(call_error)
+                })
+            }
+            (args, _) => {
+                let func = self.0.dyn_func(export).map_err(resolve_error)?;
+                let args: Vec<types::Value> = args.iter().map(to_wasmer).collect();
+                Box::new(move || {
+                    let results = func.call(&args).map_err(call_error)?;
+                    results.first().map(from_wasmer).transpose()
+                })
+            }
+        };
+        Ok(prepared)
+    }
+}
@@ -0,0 +1,64 @@
+use super::{Call, Error, Instance, Prepared, Runtime, Value};
+
+use ::wasmi::{ImportsBuilder, ModuleInstance, ModuleRef, NopExternals, RuntimeValue};
+
+pub struct Wasmi;
+
+impl Runtime for Wasmi {
+    fn name(&self) -> &str {
+        "wasmi"
+    }
+
+    fn instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Box<dyn Instance + 'a>, Error> {
+        let module =
+            ::wasmi::Module::from_buffer(wasm).map_err(|e| Error::Compile(e.to_string()))?;
+        let instance = ModuleInstance::new(&module, &ImportsBuilder::default())
+            .map_err(|e| Error::Instantiate(e.to_string()))?
+            .assert_no_start();
+        Ok(Box::new(WasmiInstance(instance)))
+    }
+}
+
+struct WasmiInstance(ModuleRef);
+
+fn to_wasmi(value: &Value) -> RuntimeValue {
+    match *value {
+        Value::I32(v) => RuntimeValue::I32(v),
+        Value::I64(v) => RuntimeValue::I64(v),
+        Value::F32(v) => RuntimeValue::F32(v.into()),
+        Value::F64(v) => RuntimeValue::F64(v.into()),
+    }
+}
+
+fn from_wasmi(value: RuntimeValue) -> Value {
+    match value {
+        RuntimeValue::I32(v) => Value::I32(v),
+        RuntimeValue::I64(v) => Value::I64(v),
+        RuntimeValue::F32(v) => Value::F32(v.to_float()),
+        RuntimeValue::F64(v) => Value::F64(v.to_float()),
+    }
+}
+
+impl Instance for WasmiInstance {
+    fn call(&self, export: &str, args: &[Value]) -> Result<Vec<Value>, Error> {
+        let args: Vec<RuntimeValue> = args.iter().map(to_wasmi).collect();
+        self.0
+            .invoke_export(export, &args, &mut NopExternals)
+            .map(|result| result.into_iter().map(from_wasmi).collect())
+            .map_err(|e| Error::Call(e.to_string()))
+    }
+
+    fn prepare<'a>(&'a self, call: &Call) -> Result<Prepared<'a>, Error> {
+        if self.0.export_by_name(call.export).is_none() {
+            return Err(Error::Resolve(format!("export {} not found", call.export)));
+        }
+        let export = call.export;
+        let args: Vec<RuntimeValue> = call.args.iter().map(to_wasmi).collect();
+        Ok(Box::new(move || {
+            self.0
+                .invoke_export(export, &args, &mut NopExternals)
+                .map(|result| result.map(from_wasmi))
+                .map_err(|e| Error::Call(e.to_string()))
+        }))
+    }
+}
@@ -0,0 +1,276 @@
+use std::cell::RefCell;
+
+use super::config::{BoundsChecks, Config, OptLevel};
+use super::{Call, Error, Instance, Prepared, Runtime, Type, Value};
+
+use ::wasmtime::{Engine, ExternType, Func, Module, SharedMemory, Store, Val};
+
+pub struct Wasmtime {
+    engine: Engine,
+    name: String,
+    /// Whether instances are metered with fuel.
+    metering: bool,
+}
+
+impl Default for Wasmtime {
+    fn default() -> Wasmtime {
+        Wasmtime {
+            engine: Engine::default(),
+            name: "wasmtime".to_string(),
+            metering: false,
+        }
+    }
+}
+
+impl Wasmtime {
+    /// Wasmtime with Cranelift configured by `config`. Cranelift has three
+    /// optimization levels, mapped to `O0`, `O2` and `Os`. Metering uses
+    /// wasmtime's fuel.
+    pub fn with_config(config: &Config) -> Result<Wasmtime, Error> {
+        let unsupported = |setting: &str| {
+            Err(Error::Unsupported(format!(
+                "wasmtime has no {} setting",
+                setting
+            )))
+        };
+        let mut wasmtime_config = ::wasmtime::Config::new();
+        match config.opt_level {
+            None => {}
+            Some(OptLevel::O0) => {
+                wasmtime_config.cranelift_opt_level(::wasmtime::OptLevel::None);
+            }
+            Some(OptLevel::O1) => return unsupported("O1"),
+            Some(OptLevel::O2) => {
+                wasmtime_config.cranelift_opt_level(::wasmtime::OptLevel::Speed);
+            }
+            Some(OptLevel::O3) => return unsupported("O3"),
+            Some(OptLevel::Os) => {
+                wasmtime_config.cranelift_opt_level(::wasmtime::OptLevel::SpeedAndSize);
+            }
+        }
+        if !config.cpu_features.is_empty() {
+            return unsupported("CPU features");
+        }
+        wasmtime_config
+            .cranelift_nan_canonicalization(config.nan_canonicalization)
+            .consume_fuel(config.metering);
+        match config.bounds_checks {
+            BoundsChecks::Default => {}
+            // Without a reservation and guard pages every access is checked.
+            BoundsChecks::Checked => {
+                wasmtime_config
+                    .memory_reservation(0)
+                    .memory_guard_size(0)
+                    .signals_based_traps(false);
+            }
+            BoundsChecks::Unchecked => return unsupported("unchecked"),
+        }
+        let engine =
+            Engine::new(&wasmtime_config).map_err(|e| Error::Unsupported(e.to_string()))?;
+        Ok(Wasmtime {
+            engine,
+            name: format!("wasmtime{}", config.suffix()),
+            metering: config.metering,
+        })
+    }
+
+    fn compile_module(&self, wasm: &[u8]) -> Result<Module, Error> {
+        Module::new(&self.engine, wasm).map_err(|e| Error::Compile(e.to_string()))
+    }
+
+    fn store(&self) -> Result<Store<()>, Error> {
+        let mut store = Store::new(&self.engine, ());
+        if self.metering {
+            store
+                .set_fuel(u64::max_value())
+                .map_err(|e| Error::Instantiate(e.to_string()))?;
+        }
+        Ok(store)
+    }
+}
+
+impl Runtime for Wasmtime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn benches_compile(&self) -> bool {
+        true
+    }
+
+    fn compile(&self, wasm: &[u8]) -> Result<(), Error> {
+        self.compile_module(wasm).map(|_| ())
+    }
+
+    fn instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Box<dyn Instance + 'a>, Error> {
+        let module = self.compile_module(wasm)?;
+        let mut store = self.store()?;
+        let instance = ::wasmtime::Instance::new(&mut store, &module, &[])
+            .map_err(|e| Error::Instantiate(e.to_string()))?;
+        Ok(Box::new(WasmtimeInstance {
+            store: RefCell::new(store),
+            instance,
+        }))
+    }
+
+    fn prepare_instantiate<'a>(&'a self, wasm: &[u8]) -> Result<Prepared<'a>, Error> {
+        let module = self.compile_module(wasm)?;
+        Ok(Box::new(move || {
+            let mut store = self.store()?;
+            ::wasmtime::Instance::new(&mut store, &module, &[])
+                .map(|_| None)
+                .map_err(|e| Error::Instantiate(e.to_string()))
+        }))
+    }
+
+    fn supports_threads(&self) -> bool {
+        true
+    }
+
+    fn instantiate_shared<'a>(
+        &'a self,
+        wasm: &[u8],
+        count: usize,
+    ) -> Result<Vec<Box<dyn Instance + Send + 'a>>, Error> {
+        let module = self.compile_module(wasm)?;
+        let memory_type = module
+            .imports()
+            .find(|import| import.module() == "env" && import.name() == "memory")
+            .and_then(|import| match import.ty() {
+                ExternType::Memory(ty) => Some(ty),
+                _ => None,
+            })
+            .ok_or_else(|| Error::Instantiate("no env.memory import".to_string()))?;
+        let memory = SharedMemory::new(&self.engine, memory_type)
+            .map_err(|e| Error::Instantiate(e.to_string()))?;
+        let mut instances: Vec<Box<dyn Instance + Send + 'a>> = Vec::new();
+        for _ in 0..count {
+            let mut store = self.store()?;
+            let instance =
+                ::wasmtime::Instance::new(&mut store, &module, &[memory.clone().into()])
+                    .map_err(|e| Error::Instantiate(e.to_string()))?;
+            instances.push(Box::new(WasmtimeInstance {
+                store: RefCell::new(store),
+                instance,
+            }));
+        }
+        Ok(instances)
+    }
+
+    fn with_config(&self, config: &Config) -> Result<Box<dyn Runtime>, Error> {
+        Ok(Box::new(Wasmtime::with_config(config)?))
+    }
+}
+
+/// Wasmtime calls need the store mutably, while `Instance` methods take
+/// `&self`.
+struct WasmtimeInstance {
+    store: RefCell<Store<()>>,
+    instance: ::wasmtime::Instance,
+}
+
+fn to_wasmtime(value: &Value) -> Val {
+    match *value {
+        Value::I32(v) => Val::I32(v),
+        Value::I64(v) => Val::I64(v),
+        Value::F32(v) => Val::F32(v.to_bits()),
+        Value::F64(v) => Val::F64(v.to_bits()),
+    }
+}
+
+fn from_wasmtime(value: &Val) -> Result<Value, Error> {
+    match *value {
+        Val::I32(v) => Ok(Value::I32(v)),
+        Val::I64(v) => Ok(Value::I64(v)),
+        Val::F32(v) => Ok(Value::F32(f32::from_bits(v))),
+        Val::F64(v) => Ok(Value::F64(f64::from_bits(v))),
+        ref other => Err(Error::Unsupported(format!("result value {:?}", other))),
+    }
+}
+
+fn call_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::Call(e.to_string())
+}
+
+fn resolve_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::Resolve(e.to_string())
+}
+
+impl WasmtimeInstance {
+    fn func(&self, export: &str) -> Result<Func, Error> {
+        self.instance
+            .get_func(&mut *self.store.borrow_mut(), export)
+            .ok_or_else(|| Error::Resolve(format!("export {} not found", export)))
+    }
+}
+
+impl Instance for WasmtimeInstance {
+    fn call(&self, export: &str, args: &[Value]) -> Result<Vec<Value>, Error> {
+        let func = self.func(export)?;
+        let mut store = self.store.borrow_mut();
+        let args: Vec<Val> = args.iter().map(to_wasmtime).collect();
+        let mut results = vec![Val::I32(0); func.ty(&*store).results().len()];
+        func.call(&mut *store, &args, &mut results)
+            .map_err(call_error)?;
+        results.iter().map(from_wasmtime).collect()
+    }
+
+    fn prepare<'a>(&'a self, call: &Call) -> Result<Prepared<'a>, Error> {
+        let func = self.func(call.export)?;
+        let store = &self.store;
+        // As with wasmer, the workloads' signatures go through typed
+        // functions and anything else through the dynamic `Func::call`.
+        let prepared: Prepared<'a> = match (call.args.as_slice(), call.result) {
+            (&[], None) => {
+                let func = func
+                    .typed::<(), ()>(&*store.borrow())
+                    .map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(&mut *store.borrow_mut(), ())
+                        .map(|()| None)
+                        .map_err(call_error)
+                })
+            }
+            (&[Value::I32(a)], None) => {
+                let func = func
+                    .typed::<i32, ()>(&*store.borrow())
+                    .map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(&mut *store.borrow_mut(), a)
+                        .map(|()| None)
+                        .map_err(call_error)
+                })
+            }
+            (&[Value::I64(a)], Some(Type::I64)) => {
+                let func = func
+                    .typed::<i64, i64>(&*store.borrow())
+                    .map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(&mut *store.borrow_mut(), a)
+                        .map(|r| Some(Value::I64(r)))
+                        .map_err(call_error)
+                })
+            }
+            (&[Value::I32(a), Value::I32(b)], Some(Type::I32)) => {
+                let func = func
+                    .typed::<(i32, i32), i32>(&*store.borrow())
+                    .map_err(resolve_error)?;
+                Box::new(move || {
+                    func.call(&mut *store.borrow_mut(), (a, b))
+                        .map(|r| Some(Value::I32(r)))
+                        .map_err(call_error)
+                })
+            }
+            (args, _) => {
+                let args: Vec<Val> = args.iter().map(to_wasmtime).collect();
+                let mut results = vec![Val::I32(0); func.ty(&*store.borrow()).results().len()];
+                Box::new(move || {
+                    func.call(&mut *store.borrow_mut(), &args, &mut results)
+                        .map_err(call_error)?;
+                    results.first().map(from_wasmtime).transpose()
+                })
+            }
+        };
+        Ok(prepared)
+    }
+}
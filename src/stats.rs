@@ -0,0 +1,247 @@
+//! Descriptive statistics over benchmark samples, and tests comparing them.
+
+use serde::{Deserialize, Serialize};
+
+/// Summary of a set of per-iteration measurements.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    /// 95% confidence interval of the mean.
+    pub ci_lower: f64,
+    pub ci_upper: f64,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[f64]) -> Option<Summary> {
+        if samples.is_empty() {
+            return None;
+        }
+        let count = samples.len();
+        let mean = mean(samples);
+        let std_dev = std_dev(samples);
+        let half_width = if count > 1 {
+            t_critical_95(count - 1) * std_dev / (count as f64).sqrt()
+        } else {
+            0.0
+        };
+        Some(Summary {
+            count,
+            mean,
+            median: median(samples),
+            std_dev,
+            min: samples.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            ci_lower: mean - half_width,
+            ci_upper: mean + half_width,
+        })
+    }
+}
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+pub fn median(samples: &[f64]) -> f64 {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Sample standard deviation (n - 1 in the denominator).
+pub fn std_dev(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    let sum_sq: f64 = samples.iter().map(|x| (x - mean) * (x - mean)).sum();
+    (sum_sq / (samples.len() - 1) as f64).sqrt()
+}
+
+/// Two-sided 95% critical value of Student's t distribution.
+pub fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179,
+        2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064,
+        2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        df if df <= TABLE.len() => TABLE[df - 1],
+        df if df <= 60 => 2.000,
+        df if df <= 120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// Standard normal cumulative distribution function.
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function, Abramowitz and Stegun 7.1.26 (absolute error below 1.5e-7).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+/// Two-sided p-value of the Mann-Whitney U test that `a` and `b` come from the
+/// same distribution, using the normal approximation with tie correction.
+///
+/// Unlike a t-test this makes no assumption about the shape of the
+/// distributions, which matters for timings with long tails.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return 1.0;
+    }
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&x| (x, true))
+        .chain(b.iter().map(|&x| (x, false)))
+        .collect();
+    all.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+
+    let n = all.len() as f64;
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j + 1 < all.len() && all[j + 1].0 == all[i].0 {
+            j += 1;
+        }
+        // Ranks are 1-based; tied values share the average rank.
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum_a += rank * all[i..=j].iter().filter(|x| x.1).count() as f64;
+        let ties = (j - i + 1) as f64;
+        tie_term += ties * ties * ties - ties;
+        i = j + 1;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean_u = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let z = ((u - mean_u).abs() - 0.5).max(0.0) / variance.sqrt();
+    (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+}
+
+/// Number of resamples drawn by [`ratio_of_means`].
+pub const BOOTSTRAP_RESAMPLES: usize = 2000;
+
+/// xorshift64*, so bootstrap intervals are reproducible from run to run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Ratio of two means with a 95% percentile bootstrap confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ratio {
+    pub estimate: f64,
+    pub ci_lower: f64,
+    pub ci_upper: f64,
+}
+
+impl Ratio {
+    /// Whether the interval excludes 1, i.e. the two differ significantly.
+    pub fn significant(&self) -> bool {
+        self.ci_lower > 1.0 || self.ci_upper < 1.0
+    }
+}
+
+/// `mean(a) / mean(b)`, with its confidence interval estimated by resampling
+/// both sets of samples independently.
+pub fn ratio_of_means(a: &[f64], b: &[f64]) -> Option<Ratio> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let estimate = mean(a) / mean(b);
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut resample_mean = |samples: &[f64]| {
+        (0..samples.len())
+            .map(|_| samples[rng.below(samples.len())])
+            .sum::<f64>()
+            / samples.len() as f64
+    };
+    let mut ratios: Vec<f64> = (0..BOOTSTRAP_RESAMPLES)
+        .map(|_| resample_mean(a) / resample_mean(b))
+        .collect();
+    ratios.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    let at = |quantile: f64| ratios[((ratios.len() - 1) as f64 * quantile).round() as usize];
+    Some(Ratio {
+        estimate,
+        ci_lower: at(0.025),
+        ci_upper: at(0.975),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::from_samples(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert!((summary.std_dev - 1.290_994).abs() < 1e-6);
+        assert!(summary.ci_lower < 2.5 && summary.ci_upper > 2.5);
+        assert!(Summary::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let a = [10.0, 11.0, 10.5, 10.2, 10.8, 11.1, 10.4, 10.9];
+        let shifted: Vec<f64> = a.iter().map(|x| x + 5.0).collect();
+        assert!(mann_whitney_u(&a, &shifted) < 0.01);
+        assert!(mann_whitney_u(&a, &a) > 0.9);
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0, 1.0]), 1.0);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_ratio_of_means() {
+        let a = [10.0, 11.0, 10.5, 10.2, 10.8, 11.1, 10.4, 10.9];
+        let doubled: Vec<f64> = a.iter().map(|x| x * 2.0).collect();
+        let ratio = ratio_of_means(&doubled, &a).unwrap();
+        assert!((ratio.estimate - 2.0).abs() < 1e-9);
+        assert!(ratio.ci_lower < 2.0 && ratio.ci_upper > 2.0);
+        assert!(ratio.significant());
+        assert!(!ratio_of_means(&a, &a).unwrap().significant());
+        assert!(ratio_of_means(&[], &a).is_none());
+    }
+}
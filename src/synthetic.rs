@@ -0,0 +1,376 @@
+//! Generated modules for compile-time scaling curves.
+//!
+//! `small_compile` and `large_compile` time two fixed modules, which says
+//! little about how compile times grow. The modules generated here vary one
+//! dimension at a time (the number of functions, the size of their bodies,
+//! their locals or the nesting of their control flow) so the compile times of
+//! every backend can be plotted against it, and super-linear growth stands
+//! out.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::filter::Filter;
+use crate::perf::criterion_mean_nanos;
+use crate::runtime::runtimes;
+use crate::OUTPUT_DIR;
+
+/// The dimensions of a generated module.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shape {
+    /// Number of functions, each calling the previous one.
+    pub functions: u32,
+    /// Number of statements in every function body, each an arithmetic
+    /// expression assigned to a local, or a call.
+    pub statements: u32,
+    /// Number of `i32` locals declared by every function, besides its
+    /// parameter, each initialized from it.
+    pub locals: u32,
+    /// Depth of the `block` and `if` instructions the statements are nested
+    /// in, which skip them when the parameter is zero.
+    pub nesting: u32,
+}
+
+impl Default for Shape {
+    fn default() -> Shape {
+        Shape {
+            functions: 5,
+            statements: 100,
+            locals: 10,
+            nesting: 4,
+        }
+    }
+}
+
+/// A dimension of [`Shape`] swept by the compile benchmark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    Functions,
+    Statements,
+    Locals,
+    Nesting,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 4] = [
+        Parameter::Functions,
+        Parameter::Statements,
+        Parameter::Locals,
+        Parameter::Nesting,
+    ];
+
+    /// Name of the benchmark sweeping this parameter, e.g.
+    /// `synthetic_locals`.
+    pub fn benchmark(self) -> &'static str {
+        match self {
+            Parameter::Functions => "synthetic_functions",
+            Parameter::Statements => "synthetic_statements",
+            Parameter::Locals => "synthetic_locals",
+            Parameter::Nesting => "synthetic_nesting",
+        }
+    }
+
+    /// The values swept, a decade apart.
+    pub fn values(self) -> Vec<u32> {
+        match self {
+            Parameter::Functions => vec![1, 10, 100, 1000],
+            Parameter::Statements => vec![10, 100, 1000, 10_000],
+            Parameter::Locals => vec![1, 10, 100, 1000],
+            Parameter::Nesting => vec![1, 10, 100, 1000],
+        }
+    }
+
+    /// The default shape with this parameter set to `value`.
+    pub fn shape(self, value: u32) -> Shape {
+        let mut shape = Shape::default();
+        match self {
+            Parameter::Functions => shape.functions = value,
+            Parameter::Statements => shape.statements = value,
+            Parameter::Locals => shape.locals = value,
+            Parameter::Nesting => shape.nesting = value,
+        }
+        shape
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_i32(out: &mut Vec<u8>, mut value: i32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_section(out: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    out.push(id);
+    write_u32(out, contents.len() as u32);
+    out.extend_from_slice(contents);
+}
+
+const I32: u8 = 0x7f;
+const EMPTY_BLOCK: u8 = 0x40;
+const BLOCK: u8 = 0x02;
+const IF: u8 = 0x04;
+const END: u8 = 0x0b;
+const BR_IF: u8 = 0x0d;
+const CALL: u8 = 0x10;
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const I32_CONST: u8 = 0x41;
+const I32_EQZ: u8 = 0x45;
+const I32_ADD: u8 = 0x6a;
+const I32_MUL: u8 = 0x6c;
+
+/// The body of function `index`, without its size.
+fn function_body(shape: &Shape, index: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    if shape.locals > 0 {
+        write_u32(&mut body, 1);
+        write_u32(&mut body, shape.locals);
+        body.push(I32);
+    } else {
+        write_u32(&mut body, 0);
+    }
+    // The parameter is local 0.
+    let locals = shape.locals + 1;
+    let local = |body: &mut Vec<u8>, opcode: u8, n: u32| {
+        body.push(opcode);
+        write_u32(body, n % locals);
+    };
+
+    // Locals start from the parameter rather than zero, so none of them is
+    // constant and every one is a live value.
+    for n in 1..locals {
+        local(&mut body, LOCAL_GET, 0);
+        body.push(I32_CONST);
+        write_i32(&mut body, n as i32);
+        body.push(I32_ADD);
+        local(&mut body, LOCAL_SET, n);
+    }
+
+    // Blocks are left when the parameter is zero and `if`s are entered when
+    // it is not, so compilers cannot drop any of the code as dead, and a
+    // non-zero argument reaches the statements.
+    for depth in 0..shape.nesting {
+        if depth % 2 == 0 {
+            body.extend_from_slice(&[BLOCK, EMPTY_BLOCK]);
+            local(&mut body, LOCAL_GET, 0);
+            body.extend_from_slice(&[I32_EQZ, BR_IF, 0]);
+        } else {
+            local(&mut body, LOCAL_GET, 0);
+            body.extend_from_slice(&[IF, EMPTY_BLOCK]);
+        }
+    }
+    for i in 0..shape.statements {
+        if index > 0 && i % 10 == 9 {
+            // With nesting, the previous function returns right away.
+            body.push(I32_CONST);
+            write_i32(&mut body, 0);
+            body.push(CALL);
+            write_u32(&mut body, index - 1);
+        } else {
+            local(&mut body, LOCAL_GET, i);
+            local(&mut body, LOCAL_GET, i.wrapping_mul(7).wrapping_add(3));
+            body.push(I32_CONST);
+            write_i32(&mut body, i as i32);
+            body.extend_from_slice(&[I32_MUL, I32_ADD]);
+        }
+        local(&mut body, LOCAL_SET, i + 1);
+    }
+    body.resize(body.len() + shape.nesting as usize, END);
+    local(&mut body, LOCAL_GET, shape.statements);
+    body.push(END);
+    body
+}
+
+/// A valid module of the given shape. All functions take and return an
+/// `i32`, and the last one is exported as `run`. With nesting, `run(0)`
+/// returns right away and `run(1)` runs the statements of the last function,
+/// whose calls to the previous one pass zero. Without nesting, every call runs
+/// the previous function's statements too, exponentially many times.
+pub fn generate(shape: &Shape) -> Vec<u8> {
+    assert!(shape.functions > 0, "a generated module needs a function");
+    let mut module = b"\0asm\x01\0\0\0".to_vec();
+
+    // One type, (i32) -> i32.
+    write_section(&mut module, 1, &[1, 0x60, 1, I32, 1, I32]);
+
+    let mut functions = Vec::new();
+    write_u32(&mut functions, shape.functions);
+    for _ in 0..shape.functions {
+        write_u32(&mut functions, 0);
+    }
+    write_section(&mut module, 3, &functions);
+
+    let mut exports = vec![1, 3];
+    exports.extend_from_slice(b"run");
+    exports.push(0);
+    write_u32(&mut exports, shape.functions - 1);
+    write_section(&mut module, 7, &exports);
+
+    let mut code = Vec::new();
+    write_u32(&mut code, shape.functions);
+    for index in 0..shape.functions {
+        let body = function_body(shape, index);
+        write_u32(&mut code, body.len() as u32);
+        code.extend_from_slice(&body);
+    }
+    write_section(&mut module, 10, &code);
+    module
+}
+
+/// Exponents `k` of `time ~ value^k` between consecutive points of a curve
+/// of `(value, mean time)` points: 1 is linear growth, 2 quadratic.
+pub fn exponents(points: &[(u32, f64)]) -> Vec<Option<f64>> {
+    points
+        .windows(2)
+        .map(|pair| {
+            let ((v0, t0), (v1, t1)) = (pair[0], pair[1]);
+            if v0 == 0 || v0 == v1 || t0 <= 0.0 || t1 <= 0.0 {
+                return None;
+            }
+            Some((t1 / t0).ln() / (f64::from(v1) / f64::from(v0)).ln())
+        })
+        .collect()
+}
+
+/// Prints the compile time curves selected by `filter` that criterion has
+/// results for, with the growth exponent between consecutive points, and
+/// writes them to `target/wasm-bench/scaling.csv`.
+pub fn report(criterion_dir: &Path, filter: &Filter) -> io::Result<()> {
+    let runtimes = runtimes();
+    let mut curves = Vec::new();
+    for &parameter in Parameter::ALL
+        .iter()
+        .filter(|p| filter.workload(p.benchmark()))
+    {
+        for runtime in runtimes
+            .iter()
+            .map(|r| r.name())
+            .filter(|&r| filter.runtime(r))
+        {
+            let points: Vec<(u32, f64)> = parameter
+                .values()
+                .into_iter()
+                .filter_map(|value| {
+                    let dir = criterion_dir
+                        .join(parameter.benchmark())
+                        .join(runtime)
+                        .join(value.to_string());
+                    criterion_mean_nanos(&dir).map(|mean| (value, mean))
+                })
+                .collect();
+            if !points.is_empty() {
+                curves.push((parameter, runtime, points));
+            }
+        }
+    }
+    if curves.is_empty() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let mut csv = fs::File::create(Path::new(OUTPUT_DIR).join("scaling.csv"))?;
+    writeln!(csv, "benchmark,backend,value,mean_nanos")?;
+    println!(
+        "{:<24}{:<16}{:>10}{:>16}{:>10}",
+        "benchmark", "backend", "value", "mean ns", "exponent"
+    );
+    for (parameter, runtime, points) in curves {
+        let exponents = exponents(&points);
+        for (i, &(value, mean)) in points.iter().enumerate() {
+            let exponent = match i.checked_sub(1).and_then(|i| exponents[i]) {
+                Some(exponent) => format!("{:.2}", exponent),
+                None => "--".to_string(),
+            };
+            println!(
+                "{:<24}{:<16}{:>10}{:>16.0}{:>10}",
+                parameter.benchmark(),
+                runtime,
+                value,
+                mean,
+                exponent
+            );
+            writeln!(
+                csv,
+                "{},{},{},{}",
+                parameter.benchmark(),
+                runtime,
+                value,
+                mean
+            )?;
+        }
+    }
+    println!("\nExponents k fit time ~ value^k since the previous value: 1 is linear.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leb128() {
+        let mut out = Vec::new();
+        write_u32(&mut out, 624_485);
+        assert_eq!(out, [0xe5, 0x8e, 0x26]);
+        out.clear();
+        write_i32(&mut out, -123_456);
+        assert_eq!(out, [0xc0, 0xbb, 0x78]);
+        out.clear();
+        write_i32(&mut out, 64);
+        assert_eq!(out, [0xc0, 0x00]);
+    }
+
+    #[test]
+    fn test_exponents() {
+        let points = [(10, 1.0), (100, 10.0), (1000, 1000.0)];
+        let exponents: Vec<f64> = exponents(&points).into_iter().map(Option::unwrap).collect();
+        assert!((exponents[0] - 1.0).abs() < 1e-9);
+        assert!((exponents[1] - 2.0).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "bench-wasmtime")]
+    #[test]
+    fn test_generated_modules_run() {
+        use crate::runtime::wasmtime::Wasmtime;
+        use crate::runtime::{Runtime, Value};
+
+        let wasmtime = Wasmtime::default();
+        for &parameter in Parameter::ALL.iter() {
+            for &value in &parameter.values()[..2] {
+                let module = generate(&parameter.shape(value));
+                let instance = wasmtime.instantiate(&module).unwrap();
+                instance.call("run", &[Value::I32(1)]).unwrap();
+            }
+        }
+
+        let shape = Shape {
+            functions: 3,
+            statements: 25,
+            locals: 4,
+            nesting: 3,
+        };
+        let instance = wasmtime.instantiate(&generate(&shape)).unwrap();
+        let run = |n| instance.call("run", &[Value::I32(n)]).unwrap();
+        assert_eq!(run(1), [Value::I32(-331_870_752)]);
+        assert_eq!(run(0), [Value::I32(0)]);
+    }
+}
@@ -0,0 +1,707 @@
+//! The benchmark workloads, shared by every harness.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+#[cfg(feature = "wasi")]
+use wasm_bench_benchmarks::wasi;
+
+use crate::runtime::{Call, Error, Instance, Prepared, Runtime, Setup, Type, Value};
+#[cfg(feature = "simd")]
+use crate::SIMD_WASM;
+#[cfg(feature = "threads")]
+use crate::THREADS_WASM;
+#[cfg(feature = "wasi")]
+use crate::WASI_WASM;
+#[cfg(feature = "wee-alloc")]
+use crate::WEE_ALLOC_WASM;
+#[cfg(feature = "proposals")]
+use crate::{BULK_MEMORY_WASM, MULTI_VALUE_WASM, MVP_WASM, REFERENCE_TYPES_WASM};
+use crate::{LARGE_WASM, OPCODE_WASMS, OUTPUT_DIR, SMALL_WASM, WASM};
+
+/// Runtime name used for the native baseline.
+pub const NATIVE: &str = "rust-native";
+
+#[derive(Clone)]
+pub enum Kind {
+    /// Compile `module` from scratch.
+    Compile,
+    /// Instantiate `module`, compiled beforehand, without imports.
+    Instantiate,
+    /// Call an export of an instance of `module`.
+    Call {
+        call: Call,
+        /// Export called once after instantiation, outside of the measurement.
+        setup: Option<&'static str>,
+        /// The same computation compiled natively, as the baseline.
+        native: Option<fn() -> Option<Value>>,
+        /// Whether `module` imports WASI and works in a sandbox directory.
+        wasi: bool,
+        /// Whether `module` uses a post-MVP proposal, such as SIMD, which not
+        /// every runtime supports.
+        proposal: bool,
+    },
+    /// Run the `main` of the Emscripten program `module` with `args` on
+    /// `iterations` fresh instances in a row, so programs as short as
+    /// `printf` are timed over more than one run of their output. Only the
+    /// runs are measured, not the instantiations.
+    Program {
+        args: Vec<&'static str>,
+        iterations: usize,
+    },
+    /// Run the reduction of `benchmarks/wat/threads.wat` on `threads`
+    /// instances of `module` sharing their memory, each on its own thread.
+    Threads {
+        threads: usize,
+        native: Option<fn() -> Option<Value>>,
+    },
+}
+
+#[derive(Clone)]
+pub struct Workload {
+    /// Benchmark group name, e.g. `fibonacci`.
+    pub name: &'static str,
+    pub module: &'static [u8],
+    pub kind: Kind,
+}
+
+impl Workload {
+    pub fn is_compile(&self) -> bool {
+        match self.kind {
+            Kind::Compile => true,
+            Kind::Instantiate | Kind::Call { .. } | Kind::Program { .. } | Kind::Threads { .. } => {
+                false
+            }
+        }
+    }
+
+    pub fn native(&self) -> Option<fn() -> Option<Value>> {
+        match self.kind {
+            Kind::Compile | Kind::Instantiate | Kind::Program { .. } => None,
+            Kind::Call { native, .. } | Kind::Threads { native, .. } => native,
+        }
+    }
+
+    /// The result every run on a runtime must return: the one of the native
+    /// version, for workloads returning an integer, which unlike a float does
+    /// not depend on how each build orders its arithmetic.
+    pub fn expected(&self) -> Option<Value> {
+        let integer = match self.kind {
+            Kind::Call { ref call, .. } => match call.result {
+                Some(Type::I32) | Some(Type::I64) => true,
+                Some(Type::F32) | Some(Type::F64) | None => false,
+            },
+            Kind::Threads { .. } => true,
+            Kind::Compile | Kind::Instantiate | Kind::Program { .. } => false,
+        };
+        if integer {
+            self.native()?()
+        } else {
+            None
+        }
+    }
+
+    /// Whether this workload is benchmarked on `runtime`.
+    pub fn runs_on(&self, runtime: &dyn Runtime) -> bool {
+        match self.kind {
+            Kind::Compile | Kind::Instantiate => runtime.benches_compile(),
+            Kind::Call { wasi, proposal, .. } => {
+                (!wasi || runtime.supports_wasi()) && (!proposal || self.probe(runtime))
+            }
+            Kind::Program { .. } => runtime.supports_emscripten(),
+            Kind::Threads { .. } => runtime.supports_threads(),
+        }
+    }
+
+    /// Whether this workload needs a wasm proposal or WASI, which not every
+    /// runtime supports, so the runtimes it does not run on are reported as
+    /// unsupported rather than skipped silently.
+    pub fn reports_unsupported(&self) -> bool {
+        match self.kind {
+            Kind::Call { proposal, wasi, .. } => proposal || wasi,
+            Kind::Threads { .. } => true,
+            Kind::Compile | Kind::Instantiate | Kind::Program { .. } => false,
+        }
+    }
+
+    /// Whether `runtime` supports the proposal `module` uses, which runtimes
+    /// without support reject when validating it.
+    fn probe(&self, runtime: &dyn Runtime) -> bool {
+        runtime.instantiate(self.module).is_ok()
+    }
+
+    /// Instantiates `module` on `runtime`, in a fresh sandbox directory for
+    /// WASI workloads, and calls the setup export.
+    pub fn instantiate<'a>(
+        &self,
+        runtime: &'a dyn Runtime,
+    ) -> Result<Box<dyn Instance + 'a>, Error> {
+        let (setup, wasi) = match self.kind {
+            Kind::Compile | Kind::Instantiate | Kind::Program { .. } | Kind::Threads { .. } => {
+                (None, false)
+            }
+            Kind::Call { setup, wasi, .. } => (setup, wasi),
+        };
+        let instance = if wasi {
+            let dir = sandbox(runtime.name())
+                .map_err(|e| Error::Instantiate(format!("cannot create sandbox: {}", e)))?;
+            runtime.instantiate_wasi(self.module, &dir)?
+        } else {
+            runtime.instantiate(self.module)?
+        };
+        if let Some(setup) = setup {
+            instance.call(setup, &[])?;
+        }
+        Ok(instance)
+    }
+
+    /// Compiles the Emscripten program `module` on `runtime`, ready to create
+    /// `iterations` instances of it and then run its `main` once on each,
+    /// repeatedly.
+    pub fn prepare_program<'a>(&self, runtime: &'a dyn Runtime) -> Result<Setup<'a>, Error> {
+        let (args, iterations) = match self.kind {
+            Kind::Program {
+                ref args,
+                iterations,
+            } => (args, iterations),
+            _ => {
+                return Err(Error::Unsupported(format!(
+                    "{} is not an Emscripten program",
+                    self.name
+                )))
+            }
+        };
+        let mut setup = runtime.prepare_emscripten(self.module, self.name, args)?;
+        Ok(Box::new(move || {
+            let mut runs = (0..iterations)
+                .map(|_| setup())
+                .collect::<Result<Vec<_>, _>>()?;
+            let run: Prepared<'a> = Box::new(move || {
+                for run in runs.iter_mut() {
+                    run()?;
+                }
+                Ok(None)
+            });
+            Ok(run)
+        }))
+    }
+
+    /// Instantiates `module` on `runtime` once per thread, plus once for the
+    /// thread waiting for them, ready to run the reduction repeatedly.
+    pub fn prepare_threads<'a>(&self, runtime: &'a dyn Runtime) -> Result<Prepared<'a>, Error> {
+        let threads = match self.kind {
+            Kind::Threads { threads, .. } => threads,
+            _ => {
+                return Err(Error::Unsupported(format!(
+                    "{} is not a threads workload",
+                    self.name
+                )))
+            }
+        };
+        let mut workers = runtime.instantiate_shared(self.module, threads + 1)?;
+        let waiter = workers.pop().unwrap();
+        waiter.call("init", &[])?;
+        Ok(Box::new(move || {
+            waiter.call("reset", &[Value::I32(threads as i32)])?;
+            let (sum, results) = thread::scope(|scope| {
+                let handles: Vec<_> = workers
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, worker)| {
+                        let args = [Value::I32(i as i32), Value::I32(threads as i32)];
+                        scope.spawn(move || worker.call("work", &args))
+                    })
+                    .collect();
+                let sum = waiter.call("wait", &[]);
+                let results: Vec<_> = handles.into_iter().map(|h| h.join()).collect();
+                (sum, results)
+            });
+            for result in results {
+                result.map_err(|_| Error::Call("worker thread panicked".to_string()))??;
+            }
+            match sum?.first() {
+                Some(&Value::I64(sum)) if sum >= 0 => Ok(Some(Value::I64(sum))),
+                _ => Err(Error::Call("threads did not finish".to_string())),
+            }
+        }))
+    }
+}
+
+/// Runs the operation `run` once, outside of the measurement, and checks that
+/// it succeeds with the `expected` result, so a broken workload stops the
+/// harness instead of being timed as a very fast run.
+pub fn check(
+    run: &mut dyn FnMut() -> Result<Option<Value>, Error>,
+    expected: Option<Value>,
+) -> Result<(), Error> {
+    let result = run()?;
+    match expected {
+        Some(expected) if result != Some(expected) => Err(Error::Call(format!(
+            "returned {:?} instead of {:?}",
+            result, expected
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Creates an empty directory for the WASI workloads of `runtime`.
+pub fn sandbox(runtime: &str) -> io::Result<PathBuf> {
+    let dir = Path::new(OUTPUT_DIR).join("sandbox").join(runtime);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn fannkuch() -> Option<Value> {
+    unsafe { wasm_bench_benchmarks::fannkuch_steps(5) };
+    None
+}
+
+fn fib() -> Option<Value> {
+    Some(Value::I64(wasm_bench_benchmarks::fib(30) as i64))
+}
+
+fn sha1() -> Option<Value> {
+    unsafe { wasm_bench_benchmarks::sha1(1000) };
+    None
+}
+
+fn sum() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::sum(1, 2)))
+}
+
+fn nbody() -> Option<Value> {
+    unsafe { wasm_bench_benchmarks::nbody::nbody_bench(5000) };
+    None
+}
+
+fn spectral_norm() -> Option<Value> {
+    Some(Value::F64(wasm_bench_benchmarks::spectral_norm_bench(100)))
+}
+
+fn mandelbrot() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::mandelbrot_bench(200)))
+}
+
+fn binary_trees() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::binary_trees_bench(10)))
+}
+
+fn fasta() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::fasta_bench(1000)))
+}
+
+fn reverse_complement() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::reverse_complement_bench()))
+}
+
+fn k_nucleotide() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::k_nucleotide_bench()))
+}
+
+fn alloc_trees() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::alloc_trees_bench(10)))
+}
+
+fn alloc_churn() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::alloc_churn_bench(20)))
+}
+
+#[cfg(feature = "simd")]
+fn simd_nbody() -> Option<Value> {
+    wasm_bench_benchmarks::simd::simd_nbody_bench(5000);
+    None
+}
+
+#[cfg(feature = "simd")]
+fn simd_dot() -> Option<Value> {
+    Some(Value::F32(wasm_bench_benchmarks::simd::simd_dot_bench(100)))
+}
+
+#[cfg(feature = "simd")]
+fn simd_find() -> Option<Value> {
+    Some(Value::I32(wasm_bench_benchmarks::simd::simd_find_bench(10)))
+}
+
+#[cfg(feature = "threads")]
+fn parallel_sum() -> Option<Value> {
+    let sum = wasm_bench_benchmarks::threads::parallel_sum(4);
+    Some(Value::I64(sum as i64))
+}
+
+#[cfg(feature = "wasi")]
+lazy_static! {
+    static ref NATIVE_SANDBOX: PathBuf = {
+        let dir = sandbox(NATIVE).expect("should create the native sandbox");
+        wasi::init(&dir).expect("should set up the native sandbox");
+        dir
+    };
+}
+
+#[cfg(feature = "wasi")]
+fn wasi_file_write() -> Option<Value> {
+    wasi::file_write(&NATIVE_SANDBOX, 10).unwrap();
+    None
+}
+
+#[cfg(feature = "wasi")]
+fn wasi_file_read() -> Option<Value> {
+    wasi::file_read(&NATIVE_SANDBOX, 10).unwrap();
+    None
+}
+
+#[cfg(feature = "wasi")]
+fn wasi_list_dir() -> Option<Value> {
+    wasi::list_dir(&NATIVE_SANDBOX, 10).unwrap();
+    None
+}
+
+#[cfg(feature = "wasi")]
+fn wasi_clock() -> Option<Value> {
+    wasi::clock(1000);
+    None
+}
+
+#[cfg(feature = "wasi")]
+fn wasi_random() -> Option<Value> {
+    wasi::random(1000);
+    None
+}
+
+fn call(
+    name: &'static str,
+    export: &'static str,
+    args: Vec<Value>,
+    result: Option<Type>,
+    native: fn() -> Option<Value>,
+) -> Workload {
+    Workload {
+        name,
+        module: WASM,
+        kind: Kind::Call {
+            call: Call {
+                export,
+                args,
+                result,
+            },
+            setup: None,
+            native: Some(native),
+            wasi: false,
+            proposal: false,
+        },
+    }
+}
+
+/// A call to an export of the WASI build, set up by `wasi_init`.
+#[cfg(feature = "wasi")]
+fn wasi_call(name: &'static str, n: i32, native: fn() -> Option<Value>) -> Workload {
+    Workload {
+        name,
+        module: WASI_WASM,
+        kind: Kind::Call {
+            call: Call {
+                export: name,
+                args: vec![Value::I32(n)],
+                result: None,
+            },
+            setup: Some("wasi_init"),
+            native: Some(native),
+            wasi: true,
+            proposal: false,
+        },
+    }
+}
+
+/// The allocation workloads `trees` and `churn`, calling exports of
+/// `module`.
+fn alloc_calls(module: &'static [u8], trees: &'static str, churn: &'static str) -> Vec<Workload> {
+    vec![
+        Workload {
+            module,
+            ..call(
+                trees,
+                "alloc_trees_bench",
+                vec![Value::I32(10)],
+                Some(Type::I32),
+                alloc_trees,
+            )
+        },
+        Workload {
+            module,
+            ..call(
+                churn,
+                "alloc_churn_bench",
+                vec![Value::I32(20)],
+                Some(Type::I32),
+                alloc_churn,
+            )
+        },
+    ]
+}
+
+/// Calls an export of the SIMD build of the benchmarks.
+#[cfg(feature = "simd")]
+fn simd_call(
+    name: &'static str,
+    export: &'static str,
+    args: Vec<Value>,
+    result: Option<Type>,
+    native: fn() -> Option<Value>,
+) -> Workload {
+    Workload {
+        name,
+        module: SIMD_WASM,
+        kind: Kind::Call {
+            call: Call {
+                export,
+                args,
+                result,
+            },
+            setup: Some("simd_init"),
+            native: Some(native),
+            wasi: false,
+            proposal: true,
+        },
+    }
+}
+
+/// Calls an export of a module of `benchmarks/wat` taking a number of
+/// iterations, without a native version. `proposal` tells whether the module
+/// uses a post-MVP proposal.
+#[cfg(feature = "proposals")]
+fn wat_call(name: &'static str, module: &'static [u8], n: i32, proposal: bool) -> Workload {
+    Workload {
+        name,
+        module,
+        kind: Kind::Call {
+            call: Call {
+                export: name,
+                args: vec![Value::I32(n)],
+                result: Some(Type::I32),
+            },
+            setup: None,
+            native: None,
+            wasi: false,
+            proposal,
+        },
+    }
+}
+
+/// Runs 100 000 iterations of the loop of an opcode microbenchmark, exported
+/// as `run` by every module of `benchmarks/wat/opcodes`.
+fn opcode_call(name: &'static str, module: &'static [u8]) -> Workload {
+    Workload {
+        name,
+        module,
+        kind: Kind::Call {
+            call: Call {
+                export: "run",
+                args: vec![Value::I32(100_000)],
+                result: Some(Type::I32),
+            },
+            setup: None,
+            native: None,
+            wasi: false,
+            proposal: false,
+        },
+    }
+}
+
+/// Calls an export working on the output of `fasta`, which `fasta_init`
+/// generates outside of the measurement.
+fn fasta_call(name: &'static str, export: &'static str, native: fn() -> Option<Value>) -> Workload {
+    Workload {
+        name,
+        module: WASM,
+        kind: Kind::Call {
+            call: Call {
+                export,
+                args: vec![],
+                result: Some(Type::I32),
+            },
+            setup: Some("fasta_init"),
+            native: Some(native),
+            wasi: false,
+            proposal: false,
+        },
+    }
+}
+
+/// Has `lua.wasm` run a script from `benchmarks/lua`, given with `-e`.
+fn lua(name: &'static str, script: &'static str) -> Workload {
+    Workload {
+        name,
+        module: LARGE_WASM,
+        kind: Kind::Program {
+            args: vec!["-e", script],
+            iterations: 1,
+        },
+    }
+}
+
+/// All workloads, in the order they are benchmarked.
+pub fn workloads() -> Vec<Workload> {
+    #[allow(unused_mut)]
+    let mut workloads = vec![
+        call("fannkuch", "fannkuch_steps", vec![Value::I32(5)], None, fannkuch),
+        call("fibonacci", "fib", vec![Value::I64(30)], Some(Type::I64), fib),
+        call("sha1", "sha1", vec![Value::I32(1000)], None, sha1),
+        call(
+            "sum",
+            "sum",
+            vec![Value::I32(1), Value::I32(2)],
+            Some(Type::I32),
+            sum,
+        ),
+        Workload {
+            name: "nbody",
+            module: WASM,
+            kind: Kind::Call {
+                call: Call {
+                    export: "nbody_bench",
+                    args: vec![Value::I32(5000)],
+                    result: None,
+                },
+                setup: Some("init"),
+                native: Some(nbody),
+                wasi: false,
+                proposal: false,
+            },
+        },
+        call(
+            "spectral_norm",
+            "spectral_norm_bench",
+            vec![Value::I32(100)],
+            Some(Type::F64),
+            spectral_norm,
+        ),
+        call(
+            "mandelbrot",
+            "mandelbrot_bench",
+            vec![Value::I32(200)],
+            Some(Type::I32),
+            mandelbrot,
+        ),
+        call(
+            "binary_trees",
+            "binary_trees_bench",
+            vec![Value::I32(10)],
+            Some(Type::I32),
+            binary_trees,
+        ),
+        call(
+            "fasta",
+            "fasta_bench",
+            vec![Value::I32(1000)],
+            Some(Type::I32),
+            fasta,
+        ),
+        fasta_call(
+            "reverse_complement",
+            "reverse_complement_bench",
+            reverse_complement,
+        ),
+        fasta_call("k_nucleotide", "k_nucleotide_bench", k_nucleotide),
+        Workload {
+            name: "small_compile",
+            module: SMALL_WASM,
+            kind: Kind::Compile,
+        },
+        Workload {
+            name: "large_compile",
+            module: LARGE_WASM,
+            kind: Kind::Compile,
+        },
+        Workload {
+            name: "instantiate",
+            module: WASM,
+            kind: Kind::Instantiate,
+        },
+        Workload {
+            name: "printf",
+            module: SMALL_WASM,
+            kind: Kind::Program {
+                args: vec![],
+                iterations: 100,
+            },
+        },
+        lua("lua_tables", include_str!("../benchmarks/lua/tables.lua")),
+        lua("lua_strings", include_str!("../benchmarks/lua/strings.lua")),
+        lua("lua_closures", include_str!("../benchmarks/lua/closures.lua")),
+        lua("lua_numeric", include_str!("../benchmarks/lua/numeric.lua")),
+    ];
+    workloads.extend(
+        OPCODE_WASMS
+            .iter()
+            .map(|&(name, module)| opcode_call(name, module)),
+    );
+    workloads.extend(alloc_calls(WASM, "alloc_trees", "alloc_churn"));
+    #[cfg(feature = "wee-alloc")]
+    workloads.extend(alloc_calls(
+        WEE_ALLOC_WASM,
+        "alloc_trees_wee",
+        "alloc_churn_wee",
+    ));
+    #[cfg(feature = "simd")]
+    workloads.extend(vec![
+        simd_call(
+            "simd_nbody",
+            "simd_nbody_bench",
+            vec![Value::I32(5000)],
+            None,
+            simd_nbody,
+        ),
+        simd_call(
+            "simd_dot",
+            "simd_dot_bench",
+            vec![Value::I32(100)],
+            Some(Type::F32),
+            simd_dot,
+        ),
+        simd_call(
+            "simd_find",
+            "simd_find_bench",
+            vec![Value::I32(10)],
+            Some(Type::I32),
+            simd_find,
+        ),
+    ]);
+    #[cfg(feature = "proposals")]
+    workloads.extend(vec![
+        wat_call("memory_copy", BULK_MEMORY_WASM, 10, true),
+        wat_call("memory_copy_loop", MVP_WASM, 10, false),
+        wat_call("memory_fill", BULK_MEMORY_WASM, 10, true),
+        wat_call("memory_fill_loop", MVP_WASM, 10, false),
+        wat_call("multi_value", MULTI_VALUE_WASM, 100_000, true),
+        wat_call("out_pointer", MVP_WASM, 100_000, false),
+        wat_call("table_get_set", REFERENCE_TYPES_WASM, 100_000, true),
+        wat_call("table_in_memory", MVP_WASM, 100_000, false),
+    ]);
+    #[cfg(feature = "threads")]
+    workloads.push(Workload {
+        name: "threads",
+        module: THREADS_WASM,
+        kind: Kind::Threads {
+            threads: 4,
+            native: Some(parallel_sum),
+        },
+    });
+    #[cfg(feature = "wasi")]
+    workloads.extend(vec![
+        wasi_call("wasi_file_write", 10, wasi_file_write),
+        wasi_call("wasi_file_read", 10, wasi_file_read),
+        wasi_call("wasi_list_dir", 10, wasi_list_dir),
+        wasi_call("wasi_clock", 1000, wasi_clock),
+        wasi_call("wasi_random", 1000, wasi_random),
+    ]);
+    workloads
+}
+
+/// Looks up a workload by name.
+pub fn workload(name: &str) -> Option<Workload> {
+    workloads().into_iter().find(|w| w.name == name)
+}
@@ -0,0 +1,72 @@
+//! Checks the native builds of the Benchmarks Game programs against their
+//! reference outputs in `benchmarks/reference`.
+
+use wasm_bench_benchmarks as benchmarks;
+
+#[test]
+fn test_spectral_norm() {
+    assert_eq!(
+        format!("{:.9}\n", benchmarks::spectral_norm(100)),
+        include_str!("../benchmarks/reference/spectralnorm-100.txt")
+    );
+}
+
+#[test]
+fn test_mandelbrot() {
+    let reference = &include_bytes!("../benchmarks/reference/mandelbrot-200.pbm")[..];
+    assert_eq!(benchmarks::mandelbrot(200), reference);
+    assert_eq!(benchmarks::mandelbrot_bench(200), benchmarks::checksum(reference));
+}
+
+#[test]
+fn test_binary_trees() {
+    let reference = include_str!("../benchmarks/reference/binarytrees-10.txt");
+    assert_eq!(benchmarks::binary_trees(10), reference);
+    assert_eq!(
+        benchmarks::binary_trees_bench(10),
+        benchmarks::checksum(reference.as_bytes())
+    );
+}
+
+#[test]
+fn test_fasta() {
+    let reference = &include_bytes!("../benchmarks/reference/fasta-1000.txt")[..];
+    assert_eq!(benchmarks::fasta(1000), reference);
+    assert_eq!(benchmarks::fasta_bench(1000), benchmarks::checksum(reference));
+}
+
+#[test]
+fn test_reverse_complement() {
+    assert_eq!(
+        benchmarks::reverse_complement(&benchmarks::fasta(1000)),
+        &include_bytes!("../benchmarks/reference/revcomp-1000.txt")[..]
+    );
+    // The export reverses the longer input of k_nucleotide.
+    assert_eq!(
+        benchmarks::reverse_complement_bench(),
+        benchmarks::checksum(&benchmarks::reverse_complement(benchmarks::input()))
+    );
+}
+
+#[test]
+fn test_k_nucleotide() {
+    assert_eq!(benchmarks::INPUT_LENGTH, 10_000);
+    let reference = include_str!("../benchmarks/reference/knucleotide-10000.txt");
+    assert_eq!(benchmarks::k_nucleotide(benchmarks::input()), reference);
+    assert_eq!(
+        benchmarks::k_nucleotide_bench(),
+        benchmarks::checksum(reference.as_bytes())
+    );
+}
+
+#[test]
+fn test_simd_nbody() {
+    let mut bodies = benchmarks::simd::bodies();
+    let before = benchmarks::simd::energy(&bodies);
+    benchmarks::simd::nbody(&mut bodies, 1000);
+    let after = benchmarks::simd::energy(&bodies);
+    assert_eq!(
+        format!("{:.9}\n{:.9}\n", before, after),
+        include_str!("../benchmarks/reference/nbody-1000.txt")
+    );
+}
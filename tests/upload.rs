@@ -0,0 +1,119 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use wasm_bench::codespeed::{self, Client};
+use wasm_bench::results::{Entry, Results, Unit};
+
+/// Serves one connection per status in `statuses` and sends the body of every
+/// request it receives over the returned channel.
+fn stub_server(statuses: Vec<u16>) -> (String, mpsc::Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for status in statuses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                let lower = header.to_lowercase();
+                if lower.starts_with("content-length:") {
+                    length = lower["content-length:".len()..].trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            sender
+                .send((request_line, String::from_utf8(body).unwrap()))
+                .unwrap();
+
+            let reply = "ok";
+            write!(
+                &stream,
+                "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                reply.len(),
+                reply
+            )
+            .unwrap();
+        }
+    });
+    (url, receiver)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8(decoded).unwrap()
+}
+
+fn results() -> Results {
+    let mut entry = Entry::new("fibonacci", "wasmer-llvm", Unit::Nanoseconds, vec![1e9, 3e9]).unwrap();
+    entry.runtime_commit = Some("abc123".to_string());
+    Results::new(vec![entry])
+}
+
+#[test]
+fn upload_retries_server_errors() {
+    let (url, requests) = stub_server(vec![503, 202]);
+    let (converted, skipped) = codespeed::convert(&results(), "ci", "master", None);
+    assert!(skipped.is_empty());
+
+    Client::new(&url)
+        .retries(2)
+        .retry_delay(Duration::from_millis(10))
+        .upload(&converted)
+        .unwrap();
+
+    let (first_line, first_body) = requests.recv().unwrap();
+    let (_, second_body) = requests.recv().unwrap();
+    assert!(first_line.starts_with("POST /result/add/json/ "));
+    assert_eq!(first_body, second_body);
+
+    assert!(first_body.starts_with("json="));
+    let json: serde_json::Value = serde_json::from_str(&percent_decode(&first_body[5..])).unwrap();
+    let result = &json[0];
+    assert_eq!(result["commitid"], "abc123");
+    assert_eq!(result["project"], "wasmer");
+    assert_eq!(result["executable"], "wasmer-llvm");
+    assert_eq!(result["benchmark"], "fibonacci");
+    assert_eq!(result["environment"], "ci");
+    assert_eq!(result["result_value"], 2.0);
+    assert_eq!(result["min"], 1.0);
+}
+
+#[test]
+fn upload_gives_up_on_client_errors() {
+    let (url, requests) = stub_server(vec![400]);
+    let (converted, _) = codespeed::convert(&results(), "ci", "master", None);
+
+    let error = Client::new(&url)
+        .retry_delay(Duration::from_millis(10))
+        .upload(&converted)
+        .unwrap_err();
+    assert!(error.to_string().contains("400"));
+    assert_eq!(requests.iter().count(), 1);
+}